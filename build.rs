@@ -1,3 +1,61 @@
+use std::env;
+use std::path::PathBuf;
+
 fn main() {
-    cc::Build::new().file("wrapper.c").compile("wrapper");
+    println!("cargo:rerun-if-changed=wrapper.c");
+    println!("cargo:rerun-if-env-changed=SDKROOT");
+    println!("cargo:rerun-if-env-changed=OSXCROSS_ROOT");
+    println!("cargo:rerun-if-env-changed=OSXCROSS_SDK");
+
+    // objc's msg_send! checks for the old `cargo-clippy` feature, and tokio's
+    // task hooks need `tokio_unstable`. A `[lints]` table would need a newer
+    // cargo than the MSRV.
+    println!("cargo:rustc-check-cfg=cfg(feature, values(\"cargo-clippy\"))");
+    println!("cargo:rustc-check-cfg=cfg(tokio_unstable)");
+
+    // For `log_build_info!`.
+    println!(
+        "cargo:rustc-env=OSLOG_TARGET={}",
+        env::var("TARGET").unwrap()
+    );
+
+    // The shim only builds against an Apple SDK.
+    if env::var("CARGO_CFG_TARGET_VENDOR").as_deref() != Ok("apple") {
+        return;
+    }
+
+    let mut build = cc::Build::new();
+    build.file("wrapper.c");
+
+    // Cross compiling from another OS with osxcross, unless the compiler has
+    // been chosen explicitly through CC or CC_<target>.
+    let target = env::var("TARGET").unwrap();
+    let compiler_overridden = [
+        "CC".to_string(),
+        "TARGET_CC".to_string(),
+        format!("CC_{}", target),
+        format!("CC_{}", target.replace('-', "_")),
+    ]
+    .iter()
+    .any(|name| env::var_os(name).is_some());
+
+    if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("macos")
+        && !cfg!(target_vendor = "apple")
+        && !compiler_overridden
+    {
+        if let Some(root) = env::var_os("OSXCROSS_ROOT") {
+            let compiler = match env::var("CARGO_CFG_TARGET_ARCH").as_deref() {
+                Ok("aarch64") => "oa64-clang",
+                _ => "o64-clang",
+            };
+            build.compiler(PathBuf::from(root).join("target/bin").join(compiler));
+        }
+    }
+
+    // cc finds the SDK with xcrun, which isn't available elsewhere.
+    if let Some(sdk) = env::var_os("SDKROOT").or_else(|| env::var_os("OSXCROSS_SDK")) {
+        build.flag("-isysroot").flag(&sdk);
+    }
+
+    build.compile("wrapper");
 }
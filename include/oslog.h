@@ -0,0 +1,50 @@
+// C interface to the oslog crate, built with its `capi` feature.
+//
+// Strings are NUL terminated UTF-8, and null pointers are ignored. Levels
+// are the OS_LOG_TYPE_* values from <os/log.h>.
+
+#ifndef OSLOG_H
+#define OSLOG_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct OsLog OsLog;
+
+// Returns a log which must be released with oslog_release, or null if either
+// name is null.
+OsLog *oslog_create(const char *subsystem, const char *category);
+void oslog_release(OsLog *log);
+
+// Logs with the log's settings applied.
+void oslog_log(const OsLog *log, uint8_t level, const char *message);
+
+// Logs through the installed OsLogger, as the log crate would with `target`.
+// Does nothing unless the crate is built with its `logger` feature.
+void oslog_log_target(const char *target, uint8_t level, const char *message);
+
+// Returns 0 if log is null.
+uint64_t oslog_signpost_id_generate(const OsLog *log);
+
+// message may be null.
+void oslog_signpost_event(const OsLog *log, uint64_t id, const char *name, const char *message);
+void oslog_signpost_interval_begin(const OsLog *log, uint64_t id, const char *name, const char *message);
+void oslog_signpost_interval_end(const OsLog *log, uint64_t id, const char *name, const char *message);
+
+// Logs referred to by integer handles, for hosts which can't hold pointers.
+// 0 is never a valid handle.
+uint64_t oslog_handle_create(const char *subsystem, const char *category);
+bool oslog_handle_release(uint64_t handle);
+bool oslog_handle_is_enabled(uint64_t handle, uint8_t level);
+// Returns false if handle isn't registered or message is null.
+bool oslog_handle_log(uint64_t handle, uint8_t level, const char *message);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
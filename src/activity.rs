@@ -0,0 +1,204 @@
+//! Grouping messages from a distributed trace into an activity.
+
+use crate::context::{self, ContextGuard};
+use crate::sys::*;
+use crate::OsLog;
+use std::ffi::CStr;
+use std::marker::PhantomData;
+
+/// The context key the trace ID is stored under.
+pub const TRACE_ID_KEY: &str = "trace_id";
+
+/// The context key the parent span ID is stored under.
+pub const SPAN_ID_KEY: &str = "span_id";
+
+/// A parsed W3C `traceparent` header, e.g.
+/// `00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TraceParent {
+    trace_id: String,
+    span_id: String,
+    flags: u8,
+}
+
+impl TraceParent {
+    /// Parses a version 00 header, or a later version's first four fields.
+    /// Returns `None` if the header is malformed or either ID is all zeros.
+    pub fn parse(header: &str) -> Option<Self> {
+        let mut fields = header.trim().split('-');
+        let version = fields.next()?;
+        let trace_id = fields.next()?;
+        let span_id = fields.next()?;
+        let flags = fields.next()?;
+
+        if !is_hex(version, 2) || version == "ff" || (version == "00" && fields.next().is_some()) {
+            return None;
+        }
+
+        if !is_hex(trace_id, 32) || !is_hex(span_id, 16) || !is_hex(flags, 2) {
+            return None;
+        }
+
+        if is_zero(trace_id) || is_zero(span_id) {
+            return None;
+        }
+
+        Some(Self {
+            trace_id: trace_id.to_string(),
+            span_id: span_id.to_string(),
+            flags: u8::from_str_radix(flags, 16).ok()?,
+        })
+    }
+
+    pub fn trace_id(&self) -> &str {
+        &self.trace_id
+    }
+
+    pub fn span_id(&self) -> &str {
+        &self.span_id
+    }
+
+    pub fn sampled(&self) -> bool {
+        self.flags & 1 != 0
+    }
+}
+
+fn is_hex(field: &str, len: usize) -> bool {
+    field.len() == len
+        && field
+            .bytes()
+            .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+fn is_zero(field: &str) -> bool {
+    field.bytes().all(|b| b == b'0')
+}
+
+/// Leaves the activity, and removes the trace's context fields, when
+/// dropped.
+#[must_use = "the activity is left when the scope is dropped"]
+pub struct ActivityScope {
+    activity: os_activity_t,
+    // The OS keeps a pointer to the state until the scope is left.
+    state: Box<os_activity_scope_state_s>,
+    _trace: ContextGuard,
+    _span: ContextGuard,
+    // Activity scopes are per thread.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ActivityScope {
+    fn drop(&mut self) {
+        unsafe {
+            os_activity_scope_leave(&mut *self.state);
+            os_release(self.activity as *mut _);
+        }
+    }
+}
+
+impl OsLog {
+    /// Enters a new "Trace" activity for a request carrying `traceparent`,
+    /// and adds its trace and span IDs to the thread's context until the
+    /// scope is dropped.
+    ///
+    /// Activity descriptions have to be string literals, so the IDs can't
+    /// be in the activity's name. Instead they're logged at the info level
+    /// when the activity begins, and appended to every message in it.
+    ///
+    /// ```no_run
+    /// use oslog::activity::TraceParent;
+    /// # use oslog::OsLog;
+    ///
+    /// let log = OsLog::new("com.example.test", "Requests");
+    /// let header = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+    ///
+    /// if let Some(traceparent) = TraceParent::parse(header) {
+    ///     let _activity = log.trace_activity(&traceparent);
+    ///     log.info("Handling request");
+    /// }
+    /// ```
+    pub fn trace_activity(&self, traceparent: &TraceParent) -> ActivityScope {
+        let activity = unsafe { wrapped_os_activity_create_trace() };
+        let mut state = Box::<os_activity_scope_state_s>::default();
+        unsafe { os_activity_scope_enter(activity, &mut *state) };
+
+        let trace = context::push(TRACE_ID_KEY, traceparent.trace_id());
+        let span = context::push(SPAN_ID_KEY, traceparent.span_id());
+
+        self.info("Trace");
+
+        ActivityScope {
+            activity,
+            state,
+            _trace: trace,
+            _span: span,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+/// Labels the current activity as a user action, e.g. a command a user ran,
+/// which Apple's diagnostic tools show specially. The system keeps a pointer
+/// to `name`, so it has to be static.
+///
+/// ```no_run
+/// oslog::activity::label_user_action(oslog::cstr!("Sync now"));
+/// ```
+pub fn label_user_action(name: &'static CStr) {
+    unsafe { os_activity_label_useraction(name.as_ptr()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn test_parse() {
+        let traceparent = TraceParent::parse(HEADER).unwrap();
+        assert_eq!(traceparent.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(traceparent.span_id(), "00f067aa0ba902b7");
+        assert!(traceparent.sampled());
+
+        assert!(TraceParent::parse(&HEADER.replace("-01", "-00"))
+            .map(|t| !t.sampled())
+            .unwrap());
+        assert!(TraceParent::parse(&format!("01{}-extra", &HEADER[2..])).is_some());
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(TraceParent::parse(""), None);
+        assert_eq!(TraceParent::parse(&HEADER.to_uppercase()), None);
+        assert_eq!(TraceParent::parse(&format!("{}-extra", HEADER)), None);
+        assert_eq!(TraceParent::parse(&format!("ff{}", &HEADER[2..])), None);
+        assert_eq!(
+            TraceParent::parse("00-00000000000000000000000000000000-00f067aa0ba902b7-01"),
+            None
+        );
+        assert_eq!(
+            TraceParent::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_label_user_action() {
+        label_user_action(crate::cstr!("Sync now"));
+    }
+
+    #[test]
+    fn test_trace_activity() {
+        let log = OsLog::new("com.example.oslog", "activity");
+        let traceparent = TraceParent::parse(HEADER).unwrap();
+
+        let scope = log.trace_activity(&traceparent);
+        let fields = context::fields();
+        assert_eq!(fields[TRACE_ID_KEY], traceparent.trace_id().into());
+        assert_eq!(fields[SPAN_ID_KEY], traceparent.span_id().into());
+
+        drop(scope);
+        assert!(context::fields().is_empty());
+    }
+}
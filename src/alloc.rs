@@ -0,0 +1,163 @@
+//! Spotting allocation hotspots in Instruments.
+
+use crate::sys::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicU64, Ordering};
+
+/// The category allocations are logged to.
+pub const CATEGORY: &str = "Allocations";
+
+const CATEGORY_CSTR: &[u8] = b"Allocations\0";
+
+/// Wraps an allocator and emits an "Allocation" signpost event, with the size
+/// and alignment as arguments, for allocations and reallocations of at least
+/// `threshold` bytes.
+///
+/// Nothing here allocates through the Rust allocator, so it can be used as
+/// the global allocator. The log is created on first use.
+///
+/// ```no_run
+/// use oslog::alloc::SignpostAllocator;
+/// use std::alloc::System;
+///
+/// #[global_allocator]
+/// static ALLOCATOR: SignpostAllocator =
+///     SignpostAllocator::new(System, oslog::cstr!("com.example.test"), 1 << 20).sample_every(10);
+/// ```
+pub struct SignpostAllocator<A = System> {
+    inner: A,
+    subsystem: &'static CStr,
+    threshold: usize,
+    sample_every: u64,
+    log: AtomicPtr<os_log_s>,
+    allocations: AtomicU64,
+}
+
+impl<A> SignpostAllocator<A> {
+    pub const fn new(inner: A, subsystem: &'static CStr, threshold: usize) -> Self {
+        Self {
+            inner,
+            subsystem,
+            threshold,
+            sample_every: 1,
+            log: AtomicPtr::new(ptr::null_mut()),
+            allocations: AtomicU64::new(0),
+        }
+    }
+
+    /// Only emits an event for every `n`th allocation over the threshold. By
+    /// default every one is.
+    pub const fn sample_every(mut self, n: u64) -> Self {
+        self.sample_every = if n == 0 { 1 } else { n };
+        self
+    }
+
+    fn log(&self) -> os_log_t {
+        let log = self.log.load(Ordering::Acquire);
+        if !log.is_null() {
+            return log;
+        }
+
+        let created = unsafe {
+            os_log_create(
+                self.subsystem.as_ptr(),
+                CATEGORY_CSTR.as_ptr() as *const c_char,
+            )
+        };
+
+        match self.log.compare_exchange(
+            ptr::null_mut(),
+            created,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => created,
+            Err(existing) => {
+                unsafe { os_release(created as *mut _) };
+                existing
+            }
+        }
+    }
+
+    fn record(&self, layout: Layout) {
+        if layout.size() < self.threshold {
+            return;
+        }
+
+        let index = self.allocations.fetch_add(1, Ordering::Relaxed);
+        if index % self.sample_every != 0 {
+            return;
+        }
+
+        let log = self.log();
+        unsafe {
+            if os_signpost_enabled(log) {
+                wrapped_os_signpost_allocation(
+                    log,
+                    OS_SIGNPOST_ID_EXCLUSIVE,
+                    layout.size(),
+                    layout.align(),
+                );
+            }
+        }
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for SignpostAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.record(layout);
+        self.inner.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        self.record(layout);
+        self.inner.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        self.record(Layout::from_size_align_unchecked(new_size, layout.align()));
+        self.inner.realloc(ptr, layout, new_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUBSYSTEM: &CStr = crate::cstr!("com.example.oslog");
+
+    #[test]
+    fn test_category() {
+        assert_eq!(
+            CStr::from_bytes_with_nul(CATEGORY_CSTR).unwrap().to_str(),
+            Ok(CATEGORY)
+        );
+    }
+
+    #[test]
+    fn test_sampling() {
+        let allocator = SignpostAllocator::new(System, SUBSYSTEM, 64).sample_every(2);
+        let small = Layout::from_size_align(16, 8).unwrap();
+        let large = Layout::from_size_align(128, 8).unwrap();
+
+        unsafe {
+            let ptr = allocator.alloc(small);
+            allocator.dealloc(ptr, small);
+            assert_eq!(allocator.allocations.load(Ordering::Relaxed), 0);
+            assert!(allocator.log.load(Ordering::Relaxed).is_null());
+
+            let ptr = allocator.alloc_zeroed(large);
+            let ptr = allocator.realloc(ptr, large, 256);
+            allocator.dealloc(ptr, Layout::from_size_align(256, 8).unwrap());
+            assert_eq!(allocator.allocations.load(Ordering::Relaxed), 2);
+            assert!(!allocator.log.load(Ordering::Relaxed).is_null());
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Marking benchmark iterations with signpost intervals, so time inside a
+//! single slow iteration can be attributed in a Time Profiler trace.
+
+use crate::signpost::SignpostId;
+use crate::OsLog;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Wraps each call to `iteration` in a signpost interval. The interval's
+/// message is the iteration's index, counting from zero.
+///
+/// ```no_run
+/// # use oslog::{bench::SignpostTimer, OsLog};
+/// let timer = SignpostTimer::new(OsLog::new("com.example.test", "Benchmarks"));
+///
+/// for _ in 0..100 {
+///     timer.iteration("parse", || {
+///         // ...
+///     });
+/// }
+/// ```
+pub struct SignpostTimer {
+    log: OsLog,
+    iterations: AtomicU64,
+}
+
+impl SignpostTimer {
+    pub fn new(log: OsLog) -> Self {
+        Self {
+            log,
+            iterations: AtomicU64::new(0),
+        }
+    }
+
+    pub fn iteration<T>(&self, name: &str, f: impl FnOnce() -> T) -> T {
+        let index = self.iterations.fetch_add(1, Ordering::Relaxed).to_string();
+        let id = SignpostId::generate(&self.log);
+
+        self.log.signpost_interval_begin(id, name, &index);
+        let result = f();
+        self.log.signpost_interval_end(id, name, &index);
+
+        result
+    }
+
+    /// The number of iterations run so far.
+    pub fn iterations(&self) -> u64 {
+        self.iterations.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(feature = "criterion")]
+pub use measurement::SignpostMeasurement;
+
+#[cfg(feature = "criterion")]
+mod measurement {
+    use crate::signpost::SignpostId;
+    use crate::OsLog;
+    use criterion::measurement::{Measurement, ValueFormatter, WallTime};
+    use std::time::{Duration, Instant};
+
+    /// A Criterion measurement which times like `WallTime`, and wraps each
+    /// sample in a signpost interval named "Sample". Criterion times batches
+    /// of iterations rather than each one, so an interval covers a batch.
+    ///
+    /// ```no_run
+    /// # use criterion::Criterion;
+    /// # use oslog::{bench::SignpostMeasurement, OsLog};
+    /// let log = OsLog::new("com.example.test", "Benchmarks");
+    /// let mut criterion = Criterion::default().with_measurement(SignpostMeasurement::new(log));
+    /// ```
+    pub struct SignpostMeasurement {
+        log: OsLog,
+    }
+
+    impl SignpostMeasurement {
+        pub fn new(log: OsLog) -> Self {
+            Self { log }
+        }
+    }
+
+    impl Measurement for SignpostMeasurement {
+        type Intermediate = (SignpostId, Instant);
+        type Value = Duration;
+
+        fn start(&self) -> Self::Intermediate {
+            let id = SignpostId::generate(&self.log);
+            self.log.signpost_interval_begin(id, "Sample", "");
+            (id, WallTime.start())
+        }
+
+        fn end(&self, (id, start): Self::Intermediate) -> Self::Value {
+            let elapsed = WallTime.end(start);
+            self.log.signpost_interval_end(id, "Sample", "");
+            elapsed
+        }
+
+        fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+            WallTime.add(v1, v2)
+        }
+
+        fn zero(&self) -> Self::Value {
+            WallTime.zero()
+        }
+
+        fn to_f64(&self, value: &Self::Value) -> f64 {
+            WallTime.to_f64(value)
+        }
+
+        fn formatter(&self) -> &dyn ValueFormatter {
+            static WALL_TIME: WallTime = WallTime;
+            WALL_TIME.formatter()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iteration() {
+        let timer = SignpostTimer::new(OsLog::new("com.example.oslog", "benchmarks"));
+        assert_eq!(timer.iteration("add", || 1 + 1), 2);
+        assert_eq!(timer.iteration("add", || 2 + 2), 4);
+        assert_eq!(timer.iterations(), 2);
+    }
+
+    #[cfg(feature = "criterion")]
+    #[test]
+    fn test_measurement() {
+        use criterion::measurement::Measurement;
+
+        let measurement = SignpostMeasurement::new(OsLog::new("com.example.oslog", "benchmarks"));
+        let start = measurement.start();
+        let elapsed = measurement.end(start);
+        assert_eq!(measurement.to_f64(&elapsed), elapsed.as_nanos() as f64);
+    }
+}
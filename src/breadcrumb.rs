@@ -0,0 +1,103 @@
+//! Marking high-level milestones, for reconstructing what a user did before
+//! reporting an issue.
+
+use crate::{activity, to_cstr, Error, Level, NulBytes, OsLog};
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::sync::{Mutex, OnceLock};
+
+/// The category breadcrumbs are logged to.
+pub const CATEGORY: &str = "Breadcrumbs";
+
+static LOG: OnceLock<OsLog> = OnceLock::new();
+
+/// Labels are kept by the system, so each distinct milestone is leaked once.
+static LABELS: Mutex<Option<HashMap<String, &'static CStr>>> = Mutex::new(None);
+
+/// The most distinct milestones which are leaked to label activities with.
+/// Later ones are still logged, but don't label the activity.
+const MAX_LABELS: usize = 256;
+
+/// Sets the subsystem breadcrumbs are logged to. Until it's called they go
+/// to the default log.
+pub fn install(subsystem: &str) -> Result<(), Error> {
+    LOG.set(OsLog::new(subsystem, CATEGORY))
+        .map_err(|_| Error::AlreadyInstalled)
+}
+
+fn label(milestone: &str) -> Option<&'static CStr> {
+    let mut labels = LABELS.lock().unwrap_or_else(|e| e.into_inner());
+    cached_label(labels.get_or_insert_with(HashMap::new), milestone)
+}
+
+fn cached_label(
+    labels: &mut HashMap<String, &'static CStr>,
+    milestone: &str,
+) -> Option<&'static CStr> {
+    if let Some(label) = labels.get(milestone) {
+        return Some(label);
+    }
+
+    if labels.len() >= MAX_LABELS {
+        return None;
+    }
+
+    let label: &'static CStr = Box::leak(
+        to_cstr(milestone, NulBytes::default())
+            .ok()?
+            .into_boxed_c_str(),
+    );
+    labels.insert(milestone.to_string(), label);
+    Some(label)
+}
+
+/// Logs `milestone`, e.g. "Finished onboarding", at the default level to the
+/// breadcrumbs category, and labels the current activity with it. Only the
+/// first 256 distinct milestones label activities, since their labels are
+/// never freed, so they shouldn't contain varying values such as IDs.
+///
+/// ```no_run
+/// oslog::breadcrumb::install("com.example.test").unwrap();
+/// oslog::breadcrumb("Finished onboarding");
+/// ```
+pub fn breadcrumb(milestone: &str) {
+    match LOG.get() {
+        Some(log) => log.with_level(Level::Default, milestone),
+        None => OsLog::global().with_level(Level::Default, milestone),
+    }
+
+    if let Some(label) = label(milestone) {
+        activity::label_user_action(label);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label() {
+        let first = label("Finished onboarding").unwrap();
+        let second = label("Finished onboarding").unwrap();
+        assert!(std::ptr::eq(first, second));
+        assert_eq!(first.to_str(), Ok("Finished onboarding"));
+    }
+
+    #[test]
+    fn test_label_cap() {
+        let mut labels = HashMap::new();
+        for i in 0..MAX_LABELS {
+            assert!(cached_label(&mut labels, &i.to_string()).is_some());
+        }
+
+        assert!(cached_label(&mut labels, "Finished onboarding").is_none());
+        assert!(cached_label(&mut labels, "0").is_some());
+        assert_eq!(labels.len(), MAX_LABELS);
+    }
+
+    #[test]
+    fn test_breadcrumb() {
+        breadcrumb("Finished onboarding");
+        breadcrumb("Nul\0byte");
+    }
+}
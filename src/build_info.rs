@@ -0,0 +1,91 @@
+use crate::event::format_value;
+use crate::{Level, OsLog};
+
+/// The target triple the crate was built for, set by build.rs.
+const TARGET: &str = env!("OSLOG_TARGET");
+
+/// Logs the calling crate's name, version and profile, the target triple,
+/// and the git hash from the `GIT_HASH` environment variable at compile time
+/// if it's set, e.g. by a build script. Usually the first thing a program
+/// logs.
+///
+/// ```no_run
+/// let log = oslog::OsLog::new("com.example.test", "Startup");
+/// oslog::log_build_info!(log);
+/// ```
+#[macro_export]
+macro_rules! log_build_info {
+    ($log:expr) => {
+        $crate::__log_build_info(
+            &$log,
+            ::std::env!("CARGO_PKG_NAME"),
+            ::std::env!("CARGO_PKG_VERSION"),
+            ::std::option_env!("GIT_HASH"),
+            if ::std::cfg!(debug_assertions) {
+                "debug"
+            } else {
+                "release"
+            },
+        )
+    };
+}
+
+fn build_info_message(
+    name: &str,
+    version: &str,
+    git_hash: Option<&str>,
+    target: &str,
+    profile: &str,
+) -> String {
+    let mut message = format!(
+        "build info crate={} version={}",
+        format_value(name),
+        format_value(version)
+    );
+
+    if let Some(git_hash) = git_hash {
+        message.push_str(&format!(" git_hash={}", format_value(git_hash)));
+    }
+
+    message.push_str(&format!(
+        " target={} profile={}",
+        format_value(target),
+        profile
+    ));
+    message
+}
+
+#[doc(hidden)]
+pub fn __log_build_info(
+    log: &OsLog,
+    name: &str,
+    version: &str,
+    git_hash: Option<&str>,
+    profile: &str,
+) {
+    let message = build_info_message(name, version, git_hash, TARGET, profile);
+    log.with_level(Level::Default, &message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_info_message() {
+        assert_eq!(
+            build_info_message("app", "1.0.0", Some("abc123"), "aarch64-apple-darwin", "release"),
+            "build info crate=app version=1.0.0 git_hash=abc123 target=aarch64-apple-darwin profile=release"
+        );
+        assert_eq!(
+            build_info_message("app", "1.0.0", None, "x86_64-apple-darwin", "debug"),
+            "build info crate=app version=1.0.0 target=x86_64-apple-darwin profile=debug"
+        );
+    }
+
+    #[test]
+    fn test_log_build_info() {
+        let log = OsLog::new("com.example.oslog", "build_info");
+        log_build_info!(log);
+    }
+}
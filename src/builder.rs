@@ -0,0 +1,146 @@
+use crate::{InvalidUtf8, Level, Metadata, NulBytes, OsLog, Truncation};
+
+/// Whether messages are shown in full, or redacted as `<private>` unless
+/// the system is configured to show private data, e.g. on a development
+/// machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Privacy {
+    #[default]
+    Public,
+    /// Only the message is private; the location appended by
+    /// `with_source_location` and the duration appended by timing methods
+    /// are dropped rather than logged publicly.
+    Private,
+}
+
+/// Configures an `OsLog`, created by `OsLog::builder`.
+///
+/// ```no_run
+/// use oslog::{Level, OsLog, Privacy, Truncation};
+///
+/// let log = OsLog::builder("com.example.test", "Accounts")
+///     .privacy(Privacy::Private)
+///     .default_level(Level::Info)
+///     .truncation(Truncation::Ellipsis, 1024)
+///     .build();
+///
+/// log.debug("Discarded");
+/// log.info("Logged, but redacted");
+/// ```
+#[derive(Debug, Clone)]
+pub struct OsLogBuilder {
+    subsystem: String,
+    category: String,
+    privacy: Privacy,
+    default_level: Option<Level>,
+    truncation: Option<(Truncation, usize)>,
+    nul_bytes: NulBytes,
+    invalid_utf8: InvalidUtf8,
+    caller_location: bool,
+    metadata: Metadata,
+}
+
+impl OsLogBuilder {
+    pub(crate) fn new(subsystem: &str, category: &str) -> Self {
+        Self {
+            subsystem: subsystem.to_string(),
+            category: category.to_string(),
+            privacy: Privacy::default(),
+            default_level: None,
+            truncation: None,
+            nul_bytes: NulBytes::default(),
+            invalid_utf8: InvalidUtf8::default(),
+            caller_location: false,
+            metadata: Metadata::new(),
+        }
+    }
+
+    pub fn privacy(mut self, privacy: Privacy) -> Self {
+        self.privacy = privacy;
+        self
+    }
+
+    /// Messages less severe than `level` are discarded without being
+    /// formatted, as if the system were configured not to keep them.
+    pub fn default_level(mut self, level: Level) -> Self {
+        self.default_level = Some(level);
+        self
+    }
+
+    /// See `OsLog::truncation`.
+    pub fn truncation(mut self, policy: Truncation, max_length: usize) -> Self {
+        self.truncation = Some((policy, max_length));
+        self
+    }
+
+    /// See `OsLog::nul_bytes`.
+    pub fn nul_bytes(mut self, policy: NulBytes) -> Self {
+        self.nul_bytes = policy;
+        self
+    }
+
+    /// See `OsLog::invalid_utf8`.
+    pub fn invalid_utf8(mut self, policy: InvalidUtf8) -> Self {
+        self.invalid_utf8 = policy;
+        self
+    }
+
+    /// See `OsLog::caller_location`.
+    pub fn caller_location(mut self, enabled: bool) -> Self {
+        self.caller_location = enabled;
+        self
+    }
+
+    /// See `OsLog::metadata`.
+    pub fn metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    pub fn build(self) -> OsLog {
+        let mut log = OsLog::new(&self.subsystem, &self.category);
+        log.privacy = self.privacy;
+        log.default_level = self.default_level;
+        log.truncation = self.truncation;
+        log.nul_bytes = self.nul_bytes;
+        log.invalid_utf8 = self.invalid_utf8;
+        log.caller_location = self.caller_location;
+        log.metadata = self.metadata;
+        log
+    }
+}
+
+impl OsLog {
+    /// Configures a new log, as an alternative to `new` followed by its
+    /// setters.
+    pub fn builder(subsystem: &str, category: &str) -> OsLogBuilder {
+        OsLogBuilder::new(subsystem, category)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let log = OsLog::builder("com.example.oslog", "builder")
+            .privacy(Privacy::Private)
+            .default_level(Level::Default)
+            .caller_location(true)
+            .build();
+
+        assert_eq!(log.privacy, Privacy::Private);
+        assert!(log.caller_location);
+
+        assert!(!log.level_is_enabled(Level::Debug));
+        assert!(!log.level_is_enabled(Level::Info));
+        if cfg!(all(target_vendor = "apple", not(feature = "off"))) {
+            assert!(log.level_is_enabled(Level::Default));
+            assert!(log.level_is_enabled(Level::Fault));
+        }
+
+        log.info("Discarded");
+        log.error("Redacted");
+    }
+}
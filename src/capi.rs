@@ -0,0 +1,234 @@
+//! `extern "C"` functions for C, C++ and Swift code in the same process,
+//! declared in `include/oslog.h`. Logs created here get the same category
+//! names as the Rust side's, and with the `logger` feature messages can be
+//! routed through the installed `OsLogger`.
+//!
+//! Strings are NUL terminated UTF-8, and invalid UTF-8 is replaced. Null
+//! pointers are ignored.
+
+use crate::{Level, OsLog, SignpostId};
+use std::ffi::CStr;
+use std::os::raw::c_char;
+
+unsafe fn to_str<'a>(string: *const c_char) -> Option<std::borrow::Cow<'a, str>> {
+    if string.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(string).to_string_lossy())
+    }
+}
+
+/// Creates a log which must be released with `oslog_release`, or returns
+/// null if either name is null.
+///
+/// # Safety
+///
+/// Both names must be null or valid C strings.
+#[no_mangle]
+pub unsafe extern "C" fn oslog_create(
+    subsystem: *const c_char,
+    category: *const c_char,
+) -> *mut OsLog {
+    match (to_str(subsystem), to_str(category)) {
+        (Some(subsystem), Some(category)) => {
+            Box::into_raw(Box::new(OsLog::new(&subsystem, &category)))
+        }
+        _ => std::ptr::null_mut(),
+    }
+}
+
+/// # Safety
+///
+/// `log` must be null or have been returned by `oslog_create`, and not
+/// already released.
+#[no_mangle]
+pub unsafe extern "C" fn oslog_release(log: *mut OsLog) {
+    if !log.is_null() {
+        drop(Box::from_raw(log));
+    }
+}
+
+/// Logs `message` at `level`, one of the OS_LOG_TYPE_* values, with the
+/// log's settings applied. Unknown levels are logged at the default level.
+///
+/// # Safety
+///
+/// `log` must be null or a live log from `oslog_create`, and `message` null
+/// or a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn oslog_log(log: *const OsLog, level: u8, message: *const c_char) {
+    if let (Some(log), Some(message)) = (log.as_ref(), to_str(message)) {
+        log.with_level(Level::from_raw(level).unwrap_or(Level::Default), &*message);
+    }
+}
+
+/// Logs `message` through the installed `OsLogger` as if by the `log` crate
+/// with `target`, so the logger's category routing and level filters
+/// apply. `level` is one of the OS_LOG_TYPE_* values.
+///
+/// # Safety
+///
+/// `target` and `message` must be null or valid C strings.
+#[cfg(feature = "logger")]
+#[no_mangle]
+pub unsafe extern "C" fn oslog_log_target(
+    target: *const c_char,
+    level: u8,
+    message: *const c_char,
+) {
+    if let (Some(target), Some(message)) = (to_str(target), to_str(message)) {
+        let level = match Level::from_raw(level) {
+            Some(Level::Debug) => log::Level::Trace,
+            Some(Level::Info) => log::Level::Debug,
+            Some(Level::Error) => log::Level::Warn,
+            Some(Level::Fault) => log::Level::Error,
+            Some(Level::Default) | None => log::Level::Info,
+        };
+
+        log::logger().log(
+            &log::Record::builder()
+                .level(level)
+                .target(&target)
+                .args(format_args!("{}", message))
+                .build(),
+        );
+    }
+}
+
+/// Without the `logger` feature there's no logger to log through, so this
+/// does nothing. It's still exported so the header links either way.
+///
+/// # Safety
+///
+/// Always safe to call.
+#[cfg(not(feature = "logger"))]
+#[no_mangle]
+pub unsafe extern "C" fn oslog_log_target(
+    _target: *const c_char,
+    _level: u8,
+    _message: *const c_char,
+) {
+}
+
+/// Generates a signpost ID unique within `log`, or returns 0 if `log` is
+/// null.
+///
+/// # Safety
+///
+/// `log` must be null or a live log from `oslog_create`.
+#[no_mangle]
+pub unsafe extern "C" fn oslog_signpost_id_generate(log: *const OsLog) -> u64 {
+    log.as_ref()
+        .map_or(0, |log| SignpostId::generate(log).as_u64())
+}
+
+/// Emits a signpost event named `name`. `message` may be null.
+///
+/// # Safety
+///
+/// `log` must be null or a live log from `oslog_create`, and `name` and
+/// `message` null or valid C strings.
+#[no_mangle]
+pub unsafe extern "C" fn oslog_signpost_event(
+    log: *const OsLog,
+    id: u64,
+    name: *const c_char,
+    message: *const c_char,
+) {
+    if let (Some(log), Some(name)) = (log.as_ref(), to_str(name)) {
+        let message = to_str(message).unwrap_or_default();
+        log.signpost_event(SignpostId::from_value(id), &name, &message);
+    }
+}
+
+/// Begins a signpost interval, which `oslog_signpost_interval_end` ends with
+/// the same ID and name. `message` may be null.
+///
+/// # Safety
+///
+/// As for `oslog_signpost_event`.
+#[no_mangle]
+pub unsafe extern "C" fn oslog_signpost_interval_begin(
+    log: *const OsLog,
+    id: u64,
+    name: *const c_char,
+    message: *const c_char,
+) {
+    if let (Some(log), Some(name)) = (log.as_ref(), to_str(name)) {
+        let message = to_str(message).unwrap_or_default();
+        log.signpost_interval_begin(SignpostId::from_value(id), &name, &message);
+    }
+}
+
+/// # Safety
+///
+/// As for `oslog_signpost_event`.
+#[no_mangle]
+pub unsafe extern "C" fn oslog_signpost_interval_end(
+    log: *const OsLog,
+    id: u64,
+    name: *const c_char,
+    message: *const c_char,
+) {
+    if let (Some(log), Some(name)) = (log.as_ref(), to_str(name)) {
+        let message = to_str(message).unwrap_or_default();
+        log.signpost_interval_end(SignpostId::from_value(id), &name, &message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = include_str!("../include/oslog.h");
+
+    #[test]
+    fn test_header() {
+        for function in &[
+            "oslog_create",
+            "oslog_release",
+            "oslog_log",
+            "oslog_log_target",
+            "oslog_signpost_id_generate",
+            "oslog_signpost_event",
+            "oslog_signpost_interval_begin",
+            "oslog_signpost_interval_end",
+            "oslog_handle_create",
+            "oslog_handle_release",
+            "oslog_handle_is_enabled",
+            "oslog_handle_log",
+        ] {
+            assert!(HEADER.contains(&format!("{}(", function)), "{}", function);
+        }
+    }
+
+    #[test]
+    fn test_level_from_raw() {
+        assert_eq!(Level::from_raw(0), Some(Level::Default));
+        assert_eq!(Level::from_raw(17), Some(Level::Fault));
+        assert_eq!(Level::from_raw(3), None);
+    }
+
+    #[test]
+    fn test_capi() {
+        let subsystem = crate::cstr!("com.example.oslog");
+        let name = crate::cstr!("Interval");
+
+        unsafe {
+            let log = oslog_create(subsystem.as_ptr(), crate::cstr!("capi").as_ptr());
+            assert!(!log.is_null());
+            assert!(oslog_create(subsystem.as_ptr(), std::ptr::null()).is_null());
+
+            oslog_log(log, 16, crate::cstr!("Hello").as_ptr());
+            oslog_log(log, 16, std::ptr::null());
+            oslog_log(std::ptr::null(), 16, crate::cstr!("Hello").as_ptr());
+
+            let id = oslog_signpost_id_generate(log);
+            oslog_signpost_interval_begin(log, id, name.as_ptr(), std::ptr::null());
+            oslog_signpost_interval_end(log, id, name.as_ptr(), crate::cstr!("Done").as_ptr());
+
+            oslog_release(log);
+            oslog_release(std::ptr::null_mut());
+        }
+    }
+}
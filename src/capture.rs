@@ -0,0 +1,223 @@
+use crate::{Error, Level, OsLog, OsLogLike, OsLogWriter};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, OnceLock};
+use std::thread::{self, JoinHandle};
+
+/// The log the capturing threads write to for the lifetime of the process.
+static LOG: OnceLock<OsLog> = OnceLock::new();
+
+/// Redirects stdout and stderr in to pipes read by background threads, which
+/// log each line to `log`, at the default and error levels respectively. This
+/// keeps `println!` output from dependencies in GUI apps, which have nowhere
+/// else for it to go. Anything written by C code is captured too.
+///
+/// When `OS_ACTIVITY_DT_MODE` is set, as it is when running from Xcode, the
+/// system copies every message to stderr, so stderr is left alone rather than
+/// logging its own output forever.
+///
+/// Fails with `Error::AlreadyInstalled`, wrapped in an `io::Error`, if the
+/// streams have already been captured.
+pub fn capture_std_streams(log: OsLog) -> io::Result<()> {
+    LOG.set(log)
+        .map_err(|_| io::Error::new(io::ErrorKind::AlreadyExists, Error::AlreadyInstalled))?;
+    let log = LOG.get().expect("the log was just set");
+
+    // Keep the real stderr for OSLOG_STDERR, which would otherwise write
+    // every message back in to the pipe.
+    let stderr = unsafe { libc::dup(libc::STDERR_FILENO) };
+    if stderr == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    crate::sink::set_stderr_fd(stderr);
+
+    redirect(libc::STDOUT_FILENO, log, Level::Default, "oslog-stdout")?;
+    if std::env::var_os("OS_ACTIVITY_DT_MODE").is_none() {
+        redirect(libc::STDERR_FILENO, log, Level::Error, "oslog-stderr")?;
+    }
+
+    Ok(())
+}
+
+fn pipe() -> io::Result<(File, File)> {
+    let mut fds = [0; 2];
+
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    unsafe { Ok((File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1]))) }
+}
+
+/// Replaces `fd` with the write end of a pipe, and logs each line read from
+/// it on a new thread.
+fn redirect<L>(fd: RawFd, log: L, level: Level, thread_name: &str) -> io::Result<()>
+where
+    L: OsLogLike + Send + 'static,
+{
+    let (mut reader, writer) = pipe()?;
+
+    if unsafe { libc::dup2(writer.as_raw_fd(), fd) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // `fd` now refers to the pipe, so this copy isn't needed.
+    drop(writer);
+
+    thread::Builder::new()
+        .name(thread_name.into())
+        .spawn(move || {
+            let mut writer = OsLogWriter::new(log, level);
+            let _ = io::copy(&mut reader, &mut writer);
+        })?;
+
+    Ok(())
+}
+
+/// The threads started by `forward_child_output`.
+pub struct ForwardedOutput {
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl ForwardedOutput {
+    /// Waits until the child has closed its output streams, usually because
+    /// it exited, and every line has been logged.
+    pub fn join(self) {
+        for thread in self.threads {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn forward_lines<L>(
+    stream: impl Read + Send + 'static,
+    pid: u32,
+    log: Arc<L>,
+    level: Level,
+) -> io::Result<JoinHandle<()>>
+where
+    L: OsLogLike + Send + Sync + 'static,
+{
+    thread::Builder::new()
+        .name(format!("oslog-child-{}", pid))
+        .spawn(move || {
+            for line in BufReader::new(stream).split(b'\n') {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+
+                let line = line.strip_suffix(b"\r").unwrap_or(&line);
+                log.with_level(
+                    level,
+                    &format!("[{}] {}", pid, String::from_utf8_lossy(line)),
+                );
+            }
+        })
+}
+
+/// Logs each line the child writes to its piped stdout and stderr at `level`,
+/// prefixed with its PID, e.g. "[1234] Listening on port 80".
+pub fn forward_child_output<L>(
+    child: &mut Child,
+    log: L,
+    level: Level,
+) -> io::Result<ForwardedOutput>
+where
+    L: OsLogLike + Send + Sync + 'static,
+{
+    let pid = child.id();
+    let log = Arc::new(log);
+    let mut threads = Vec::new();
+
+    if let Some(stdout) = child.stdout.take() {
+        threads.push(forward_lines(stdout, pid, log.clone(), level)?);
+    }
+
+    if let Some(stderr) = child.stderr.take() {
+        threads.push(forward_lines(stderr, pid, log, level)?);
+    }
+
+    Ok(ForwardedOutput { threads })
+}
+
+/// Spawns `command` with its stdout and stderr piped to
+/// `forward_child_output`.
+pub fn spawn_forwarding<L>(
+    command: &mut Command,
+    log: L,
+    level: Level,
+) -> io::Result<(Child, ForwardedOutput)>
+where
+    L: OsLogLike + Send + Sync + 'static,
+{
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let output = forward_child_output(&mut child, log, level)?;
+    Ok((child, output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockLog;
+    use std::io::Write;
+    use std::time::Duration;
+
+    #[test]
+    fn test_redirect() {
+        let log: &'static MockLog = Box::leak(Box::new(MockLog::new()));
+        let mut target = File::open("/dev/null").unwrap();
+
+        redirect(target.as_raw_fd(), log, Level::Error, "oslog-test").unwrap();
+        writeln!(target, "One\nTwo").unwrap();
+
+        for _ in 0..100 {
+            if log.messages().len() == 2 {
+                break;
+            }
+
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(
+            log.messages(),
+            vec![
+                (Level::Error, "One".to_string()),
+                (Level::Error, "Two".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_forward_child_output() {
+        let log = Arc::new(MockLog::new());
+
+        let (mut child, output) = spawn_forwarding(
+            Command::new("sh").args(["-c", "echo One; echo Two >&2"]),
+            log.clone(),
+            Level::Info,
+        )
+        .unwrap();
+
+        let pid = child.id();
+        child.wait().unwrap();
+        output.join();
+
+        let mut messages = log.messages();
+        messages.sort_by(|a, b| a.1.cmp(&b.1));
+
+        assert_eq!(
+            messages,
+            vec![
+                (Level::Info, format!("[{}] One", pid)),
+                (Level::Info, format!("[{}] Two", pid)),
+            ]
+        );
+    }
+}
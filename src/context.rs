@@ -0,0 +1,150 @@
+//! Fields appended to every message logged on the current thread, e.g. a
+//! request ID, without passing them down the call tree.
+//!
+//! ```no_run
+//! # use oslog::OsLog;
+//! let log = OsLog::new("com.example.test", "Requests");
+//! let _request = oslog::context::push("request_id", 42);
+//!
+//! // Logged as "Handling request request_id=42".
+//! log.info("Handling request");
+//! ```
+
+use crate::metadata::{Metadata, MetadataValue};
+use crate::signpost::SignpostId;
+use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The context key `correlate` stores the ID under.
+pub const CORRELATION_ID_KEY: &str = "correlation_id";
+
+thread_local! {
+    static CONTEXT: RefCell<Vec<(String, MetadataValue)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Removes the field it was returned for, and any pushed after it, when
+/// dropped.
+#[must_use = "the field is removed when the guard is dropped"]
+pub struct ContextGuard {
+    len: usize,
+    // The context is per thread.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        CONTEXT.with(|context| context.borrow_mut().truncate(self.len));
+    }
+}
+
+/// Adds `key` to the current thread's context until the guard is dropped.
+/// A field shadows earlier ones with the same key.
+pub fn push(key: &str, value: impl Into<MetadataValue>) -> ContextGuard {
+    CONTEXT.with(|context| {
+        let mut context = context.borrow_mut();
+        let len = context.len();
+        context.push((key.to_string(), value.into()));
+
+        ContextGuard {
+            len,
+            _not_send: PhantomData,
+        }
+    })
+}
+
+/// Returns the current thread's context.
+pub fn fields() -> Metadata {
+    CONTEXT.with(|context| context.borrow().iter().cloned().collect())
+}
+
+/// A random ID for joining the messages and signposts of one request or
+/// activity, formatted as 16 hex digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CorrelationId(u64);
+
+impl CorrelationId {
+    pub fn generate() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        // RandomState is randomly seeded, and the counter keeps IDs unique
+        // even if seeds repeat.
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+        Self(hasher.finish())
+    }
+
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+
+    /// The ID as a signpost ID, so intervals can be matched up with
+    /// messages.
+    pub fn signpost_id(self) -> SignpostId {
+        SignpostId::from_value(self.0)
+    }
+}
+
+impl fmt::Display for CorrelationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Generates a correlation ID and adds it to the current thread's context
+/// under `CORRELATION_ID_KEY` until the guard is dropped.
+pub fn correlate() -> (CorrelationId, ContextGuard) {
+    let id = CorrelationId::generate();
+    let guard = push(CORRELATION_ID_KEY, id.to_string());
+    (id, guard)
+}
+
+pub(crate) fn is_empty() -> bool {
+    CONTEXT.with(|context| context.borrow().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OsLog;
+
+    #[test]
+    fn test_push() {
+        assert!(is_empty());
+
+        let _request = push("request_id", 42);
+        {
+            let _user = push("user", "alice");
+            let _shadowed = push("request_id", 43);
+            assert_eq!(fields().len(), 2);
+            assert_eq!(fields()["request_id"], MetadataValue::Int(43));
+        }
+
+        assert_eq!(fields().len(), 1);
+        assert_eq!(fields()["request_id"], MetadataValue::Int(42));
+
+        let log = OsLog::new("com.example.oslog", "context").metadata_value("service", "api");
+        assert_eq!(log.locate("Hi"), "Hi request_id=42 service=api");
+        log.info("Hi");
+
+        std::thread::spawn(|| assert!(is_empty())).join().unwrap();
+    }
+
+    #[test]
+    fn test_correlate() {
+        let (id, guard) = correlate();
+        assert_ne!(id, CorrelationId::generate());
+        assert_eq!(id.to_string().len(), 16);
+        assert_eq!(id.signpost_id(), SignpostId::from_value(id.as_u64()));
+        assert_eq!(
+            fields()[CORRELATION_ID_KEY],
+            MetadataValue::String(id.to_string())
+        );
+
+        drop(guard);
+        assert!(is_empty());
+    }
+}
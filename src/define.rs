@@ -0,0 +1,127 @@
+use crate::OsLog;
+use std::ops::Deref;
+use std::sync::OnceLock;
+
+/// An `OsLog` which is created the first time it's used, defined with
+/// `define_log!`.
+pub struct LazyLog {
+    log: OnceLock<OsLog>,
+    subsystem: &'static str,
+    category: &'static str,
+}
+
+impl LazyLog {
+    #[doc(hidden)]
+    pub const fn new(subsystem: &'static str, category: &'static str) -> Self {
+        Self {
+            log: OnceLock::new(),
+            subsystem,
+            category,
+        }
+    }
+}
+
+impl Deref for LazyLog {
+    type Target = OsLog;
+
+    fn deref(&self) -> &OsLog {
+        self.log
+            .get_or_init(|| OsLog::new(self.subsystem, self.category))
+    }
+}
+
+/// Defines a static `LazyLog`, which creates its `OsLog` the first time it's
+/// used, so modules can share a log without passing it around. The subsystem
+/// and category must be constants, and are checked with `validate_subsystem`
+/// and `validate_category` at compile time.
+///
+/// ```no_run
+/// oslog::define_log!(NET_LOG, "com.example.test", "Networking");
+/// oslog::define_log!(pub(crate) DB_LOG, "com.example.test", "Database");
+///
+/// NET_LOG.info("Connected");
+/// DB_LOG.error("Query failed");
+/// ```
+#[macro_export]
+macro_rules! define_log {
+    ($vis:vis $name:ident, $subsystem:expr, $category:expr $(,)?) => {
+        const _: () = $crate::__assert_valid_names($subsystem, $category);
+        $vis static $name: $crate::LazyLog = $crate::LazyLog::new($subsystem, $category);
+    };
+}
+
+/// Defines a static `OsLog` for each category in one subsystem, like
+/// `define_log!`, so categories are referred to by name rather than by
+/// string.
+///
+/// ```no_run
+/// oslog::categories! {
+///     "com.example.test";
+///     pub SETTINGS = "Settings",
+///     pub(crate) PARSING = "Parsing",
+/// }
+///
+/// SETTINGS.info("Loaded");
+/// PARSING.error("Unexpected token");
+/// ```
+#[macro_export]
+macro_rules! categories {
+    ($subsystem:expr; $($vis:vis $name:ident = $category:expr),+ $(,)?) => {
+        $(
+            $crate::define_log!($vis $name, $subsystem, $category);
+        )+
+    };
+}
+
+/// Converts a string literal to a `&'static CStr` at compile time, failing
+/// to compile if it contains a NUL byte.
+///
+/// ```no_run
+/// use std::ffi::CStr;
+///
+/// const SUBSYSTEM: &CStr = oslog::cstr!("com.example.test");
+/// ```
+#[macro_export]
+macro_rules! cstr {
+    ($string:literal) => {{
+        const CSTR: &::std::ffi::CStr =
+            match ::std::ffi::CStr::from_bytes_with_nul(::std::concat!($string, "\0").as_bytes()) {
+                ::std::result::Result::Ok(cstr) => cstr,
+                ::std::result::Result::Err(_) => ::std::panic!("string contains a NUL byte"),
+            };
+        CSTR
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    define_log!(TEST_LOG, "com.example.oslog", "define");
+    define_log!(pub(crate) SHARED_LOG, "com.example.oslog", "define",);
+
+    categories! {
+        "com.example.oslog";
+        SETTINGS = "Settings",
+        pub(crate) PARSING = "Parsing",
+    }
+
+    #[test]
+    fn test_categories() {
+        SETTINGS.info("Info");
+        PARSING.error("Error");
+        assert!(*SETTINGS != *PARSING);
+    }
+
+    #[test]
+    fn test_define_log() {
+        TEST_LOG.info("Info");
+        SHARED_LOG.error("Error");
+        assert!(std::ptr::eq(&*TEST_LOG, &*TEST_LOG));
+    }
+
+    #[test]
+    fn test_cstr() {
+        const SUBSYSTEM: &std::ffi::CStr = cstr!("com.example.oslog");
+        assert_eq!(SUBSYSTEM.to_str(), Ok("com.example.oslog"));
+        assert_eq!(cstr!("").to_bytes(), b"");
+    }
+}
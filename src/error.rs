@@ -0,0 +1,52 @@
+use crate::NameError;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The message was longer than the configured maximum length.
+    MessageTooLong { length: usize, max_length: usize },
+    /// The message contained a NUL byte at `position`.
+    InteriorNul { position: usize },
+    /// A global hook or logger has already been installed.
+    AlreadyInstalled,
+    /// The subsystem name was rejected by `validate_subsystem`.
+    InvalidSubsystem(NameError),
+    /// The category name was rejected by `validate_category`.
+    InvalidCategory(NameError),
+    /// The category is one of `RESERVED_CATEGORIES`.
+    ReservedCategory(String),
+    /// The log store couldn't be read.
+    #[cfg(feature = "store")]
+    Store(String),
+    /// A value couldn't be serialized.
+    #[cfg(feature = "serde")]
+    Serialize(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MessageTooLong { length, max_length } => write!(
+                f,
+                "message is {} bytes long, the maximum is {}",
+                length, max_length
+            ),
+            Self::InteriorNul { position } => {
+                write!(f, "message contains a NUL byte at position {}", position)
+            }
+            Self::AlreadyInstalled => write!(f, "already installed"),
+            Self::InvalidSubsystem(error) => write!(f, "invalid subsystem: {}", error),
+            Self::InvalidCategory(error) => write!(f, "invalid category: {}", error),
+            Self::ReservedCategory(category) => {
+                write!(f, "{} is a reserved category", category)
+            }
+            #[cfg(feature = "store")]
+            Self::Store(description) => write!(f, "couldn't read the log store: {}", description),
+            #[cfg(feature = "serde")]
+            Self::Serialize(description) => write!(f, "couldn't serialize: {}", description),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,102 @@
+use crate::{Level, OsLog};
+use std::fmt::{Display, Write};
+
+/// Quotes `value` if it's empty or contains whitespace, quotes or `=`, the
+/// way logfmt does.
+pub(crate) fn format_value(value: &str) -> String {
+    if !value.is_empty()
+        && !value
+            .chars()
+            .any(|c| c.is_whitespace() || c == '"' || c == '=')
+    {
+        return value.to_string();
+    }
+
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// A message built from a name and `key=value` fields, rendered in the order
+/// the fields were added, e.g. `cache_miss key=user:1 latency_ms=12`. Created
+/// with `OsLog::event`.
+#[must_use = "events aren't logged until `emit` is called"]
+pub struct Event<'a> {
+    log: &'a OsLog,
+    message: String,
+}
+
+impl<'a> Event<'a> {
+    pub fn field(mut self, key: &str, value: impl Display) -> Self {
+        let _ = write!(
+            self.message,
+            " {}={}",
+            key,
+            format_value(&value.to_string())
+        );
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    #[track_caller]
+    pub fn emit(self, level: Level) {
+        self.log.with_level(level, &self.message);
+    }
+}
+
+impl OsLog {
+    /// ```no_run
+    /// # use oslog::{Level, OsLog};
+    /// let log = OsLog::new("com.example.test", "Cache");
+    /// log.event("cache_miss")
+    ///     .field("key", "user:1")
+    ///     .field("latency_ms", 12)
+    ///     .emit(Level::Info);
+    /// ```
+    pub fn event(&self, name: &str) -> Event<'_> {
+        Event {
+            log: self,
+            message: name.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_value() {
+        assert_eq!(format_value("plain"), "plain");
+        assert_eq!(format_value(""), "\"\"");
+        assert_eq!(format_value("two words"), "\"two words\"");
+        assert_eq!(format_value("a=b"), "\"a=b\"");
+        assert_eq!(format_value("say \"hi\"\n"), "\"say \\\"hi\\\"\\n\"");
+    }
+
+    #[test]
+    fn test_event() {
+        let log = OsLog::new("com.example.oslog", "event");
+        let event = log
+            .event("cache_miss")
+            .field("key", "user 1")
+            .field("latency_ms", 12);
+
+        assert_eq!(event.message(), "cache_miss key=\"user 1\" latency_ms=12");
+        event.emit(Level::Info);
+    }
+}
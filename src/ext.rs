@@ -0,0 +1,167 @@
+use crate::{Level, OsLogLike};
+use std::error::Error;
+use std::fmt::{Display, Write};
+
+/// Formats `error` followed by each of its sources on their own line.
+pub(crate) fn format_error_chain(error: &dyn Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+
+    if source.is_some() {
+        message.push_str("\nCaused by:");
+    }
+
+    while let Some(cause) = source {
+        write!(message, "\n    {}", cause).unwrap();
+        source = cause.source();
+    }
+
+    message
+}
+
+/// Logs errors inline while still propagating them with `?`.
+pub trait ResultExt {
+    /// Logs the error, if any, at the error level.
+    fn log_err(self, log: &impl OsLogLike) -> Self;
+
+    /// Logs the error, if any, at the error level prefixed with `message`.
+    fn log_err_with(self, log: &impl OsLogLike, message: &str) -> Self;
+}
+
+impl<T, E: Display> ResultExt for Result<T, E> {
+    fn log_err(self, log: &impl OsLogLike) -> Self {
+        if let Err(e) = &self {
+            log.error(&e.to_string());
+        }
+
+        self
+    }
+
+    fn log_err_with(self, log: &impl OsLogLike, message: &str) -> Self {
+        if let Err(e) = &self {
+            log.error(&format!("{}: {}", message, e));
+        }
+
+        self
+    }
+}
+
+/// Logs unexpected `None`s without breaking method chains.
+pub trait OptionExt {
+    /// Logs `message` at the error level if the option is `None`.
+    fn log_none(self, log: &impl OsLogLike, message: &str) -> Self;
+
+    /// Logs `message` at `level` if the option is `None`.
+    fn log_none_at(self, log: &impl OsLogLike, level: Level, message: &str) -> Self;
+}
+
+impl<T> OptionExt for Option<T> {
+    fn log_none(self, log: &impl OsLogLike, message: &str) -> Self {
+        self.log_none_at(log, Level::Error, message)
+    }
+
+    fn log_none_at(self, log: &impl OsLogLike, level: Level, message: &str) -> Self {
+        if self.is_none() {
+            log.with_level(level, message);
+        }
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockLog;
+
+    #[test]
+    fn test_log_err() {
+        let log = MockLog::new();
+
+        let ok: Result<u8, String> = Ok(1);
+        assert_eq!(ok.log_err(&log), Ok(1));
+
+        let err: Result<u8, String> = Err("Oops".into());
+        assert_eq!(err.log_err(&log), Err("Oops".into()));
+
+        assert_eq!(log.messages(), vec![(Level::Error, "Oops".to_string())]);
+    }
+
+    #[test]
+    fn test_log_err_with() {
+        let log = MockLog::new();
+
+        let ok: Result<u8, String> = Ok(1);
+        assert_eq!(ok.log_err_with(&log, "Reading"), Ok(1));
+
+        let err: Result<u8, String> = Err("Oops".into());
+        assert_eq!(err.log_err_with(&log, "Reading"), Err("Oops".into()));
+
+        assert_eq!(
+            log.messages(),
+            vec![(Level::Error, "Reading: Oops".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_log_none() {
+        let log = MockLog::new();
+
+        assert_eq!(Some(1).log_none(&log, "Missing"), Some(1));
+        assert_eq!(None::<u8>.log_none(&log, "Missing"), None);
+        assert_eq!(
+            None::<u8>.log_none_at(&log, Level::Default, "Missing default"),
+            None
+        );
+
+        assert_eq!(
+            log.messages(),
+            vec![
+                (Level::Error, "Missing".to_string()),
+                (Level::Default, "Missing default".to_string())
+            ]
+        );
+    }
+
+    #[derive(Debug)]
+    struct Chained(&'static str, Option<Box<Chained>>);
+
+    impl Display for Chained {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(self.0)
+        }
+    }
+
+    impl Error for Chained {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            self.1
+                .as_ref()
+                .map(|e| e.as_ref() as &(dyn Error + 'static))
+        }
+    }
+
+    #[test]
+    fn test_format_error_chain() {
+        assert_eq!(format_error_chain(&Chained("Top", None)), "Top");
+
+        let error = Chained(
+            "Couldn't load settings",
+            Some(Box::new(Chained(
+                "Couldn't read file",
+                Some(Box::new(Chained("Permission denied", None))),
+            ))),
+        );
+
+        assert_eq!(
+            format_error_chain(&error),
+            "Couldn't load settings\nCaused by:\n    Couldn't read file\n    Permission denied"
+        );
+
+        let log = MockLog::new();
+        log.error_chain(&error);
+        assert_eq!(
+            log.messages(),
+            vec![(Level::Error, format_error_chain(&error))]
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{LogMessage, OsLog};
+
+impl OsLog {
+    /// Logs `message` as a fault, flushes the installed `log` logger, and
+    /// aborts the process, for invariant violations a daemon can't recover
+    /// from. Unlike panicking, nothing is unwound first.
+    #[track_caller]
+    pub fn fault_and_abort<'a>(&self, message: impl Into<LogMessage<'a>>) -> ! {
+        self.fault(message);
+
+        #[cfg(feature = "logger")]
+        log::logger().flush();
+
+        std::process::abort()
+    }
+}
+
+/// Logs a formatted fault to `log` and aborts the process, using
+/// `OsLog::fault_and_abort`.
+///
+/// ```no_run
+/// # use oslog::OsLog;
+/// let log = OsLog::new("com.example.test", "Storage");
+/// oslog::fatal!(log, "Journal {} is corrupt", 3);
+/// ```
+#[macro_export]
+macro_rules! fatal {
+    ($log:expr, $($arg:tt)+) => {
+        $log.fault_and_abort(::std::format_args!($($arg)+))
+    };
+}
+
+/// Logs a fault with the condition's source text and location when `cond`
+/// is false, then panics in debug builds. Release builds carry on, so the
+/// failure is reported without taking the process down.
+///
+/// ```no_run
+/// # use oslog::OsLog;
+/// let log = OsLog::new("com.example.test", "Storage");
+/// let free = 10;
+/// oslog::fault_assert!(free > 0, log);
+/// oslog::fault_assert!(free < 100, log, "{} pages free", free);
+/// ```
+#[macro_export]
+macro_rules! fault_assert {
+    ($cond:expr, $log:expr $(,)?) => {
+        $crate::fault_assert!($cond, $log, "")
+    };
+    ($cond:expr, $log:expr, $($arg:tt)+) => {
+        if !$cond {
+            let mut message = ::std::format!(
+                "assertion failed: {} at {}:{}",
+                ::std::stringify!($cond),
+                ::std::file!(),
+                ::std::line!(),
+            );
+            let details = ::std::format!($($arg)+);
+            if !details.is_empty() {
+                message.push_str(": ");
+                message.push_str(&details);
+            }
+
+            $log.fault(&message);
+            if ::std::cfg!(debug_assertions) {
+                ::std::panic!("{}", message);
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Level, MockLog, OsLog, OsLogLike};
+
+    // Aborting can't be tested in process, but the macro has to expand to a
+    // diverging expression.
+    #[allow(dead_code)]
+    fn diverges(log: &OsLog, journal: u32) -> u32 {
+        if journal > 0 {
+            return journal;
+        }
+        fatal!(log, "Journal {} is corrupt", journal)
+    }
+
+    #[test]
+    fn test_fault_assert() {
+        let log = MockLog::new();
+        let free = 10;
+        fault_assert!(free > 0, log);
+        assert!(log.messages().is_empty());
+
+        let result = std::panic::catch_unwind(|| {
+            fault_assert!(free > 100, log, "{} pages free", free);
+        });
+        assert_eq!(result.is_err(), cfg!(debug_assertions));
+
+        let (level, message) = &log.messages()[0];
+        assert_eq!(*level, Level::Fault);
+        assert!(message.starts_with("assertion failed: free > 100 at src/fatal.rs:"));
+        assert!(message.ends_with(": 10 pages free"));
+    }
+}
@@ -0,0 +1,132 @@
+//! Logs referred to by integer handles, for hosts which embed a Rust library
+//! and can't hold pointers across the boundary, e.g. through uniffi. The
+//! `oslog_handle_*` functions are declared in `include/oslog.h`, and a
+//! library can hand logs it configured itself to the host with `register`.
+//!
+//! Handles are never reused, and 0 is never a valid handle.
+
+use crate::{Level, OsLog};
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
+
+static HANDLES: Mutex<Option<HashMap<u64, Arc<OsLog>>>> = Mutex::new(None);
+
+fn with_handles<R>(f: impl FnOnce(&mut HashMap<u64, Arc<OsLog>>) -> R) -> R {
+    let mut handles = HANDLES.lock().unwrap_or_else(|e| e.into_inner());
+    f(handles.get_or_insert_with(HashMap::new))
+}
+
+/// Keeps `log` until the returned handle is released.
+pub fn register(log: OsLog) -> u64 {
+    let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+    with_handles(|handles| handles.insert(handle, Arc::new(log)));
+    handle
+}
+
+/// Returns the log for `handle`, which stays valid even if the handle is
+/// released meanwhile.
+pub fn get(handle: u64) -> Option<Arc<OsLog>> {
+    with_handles(|handles| handles.get(&handle).cloned())
+}
+
+/// Returns false if `handle` isn't registered.
+pub fn release(handle: u64) -> bool {
+    with_handles(|handles| handles.remove(&handle)).is_some()
+}
+
+/// Creates a log, returning 0 if either name is null.
+///
+/// # Safety
+///
+/// Both names must be null or valid C strings.
+#[no_mangle]
+pub unsafe extern "C" fn oslog_handle_create(
+    subsystem: *const c_char,
+    category: *const c_char,
+) -> u64 {
+    if subsystem.is_null() || category.is_null() {
+        return 0;
+    }
+
+    register(OsLog::new(
+        &CStr::from_ptr(subsystem).to_string_lossy(),
+        &CStr::from_ptr(category).to_string_lossy(),
+    ))
+}
+
+/// Returns false if `handle` isn't registered.
+#[no_mangle]
+pub extern "C" fn oslog_handle_release(handle: u64) -> bool {
+    release(handle)
+}
+
+/// Returns whether `level`, one of the OS_LOG_TYPE_* values, is enabled for
+/// `handle`'s log. Unknown handles are never enabled.
+#[no_mangle]
+pub extern "C" fn oslog_handle_is_enabled(handle: u64, level: u8) -> bool {
+    match (get(handle), Level::from_raw(level)) {
+        (Some(log), Some(level)) => log.level_is_enabled(level),
+        _ => false,
+    }
+}
+
+/// Logs `message` at `level`, one of the OS_LOG_TYPE_* values, returning
+/// false if `handle` isn't registered or `message` is null. Unknown levels
+/// are logged at the default level.
+///
+/// # Safety
+///
+/// `message` must be null or a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn oslog_handle_log(handle: u64, level: u8, message: *const c_char) -> bool {
+    match get(handle) {
+        Some(log) if !message.is_null() => {
+            log.with_level(
+                Level::from_raw(level).unwrap_or(Level::Default),
+                &*CStr::from_ptr(message).to_string_lossy(),
+            );
+            true
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register() {
+        let handle = register(OsLog::new("com.example.oslog", "handle"));
+        assert_ne!(handle, 0);
+        assert!(get(handle).is_some());
+        assert!(release(handle));
+        assert!(get(handle).is_none());
+        assert!(!release(handle));
+        assert_ne!(register(OsLog::disabled()), handle);
+    }
+
+    #[test]
+    fn test_handle_functions() {
+        let subsystem = crate::cstr!("com.example.oslog");
+        let message = crate::cstr!("Hello");
+
+        unsafe {
+            let handle = oslog_handle_create(subsystem.as_ptr(), crate::cstr!("handle").as_ptr());
+            assert_ne!(handle, 0);
+            assert_eq!(oslog_handle_create(subsystem.as_ptr(), std::ptr::null()), 0);
+
+            assert!(oslog_handle_log(handle, 16, message.as_ptr()));
+            assert!(!oslog_handle_log(handle, 16, std::ptr::null()));
+            assert!(!oslog_handle_is_enabled(0, 16));
+
+            assert!(oslog_handle_release(handle));
+            assert!(!oslog_handle_log(handle, 16, message.as_ptr()));
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use crate::location::SourceLocation;
+use crate::sys::*;
+use crate::Level;
+use std::ffi::{c_void, CStr};
+use std::os::raw::c_char;
+
+/// The executable or dylib Console attributes messages to.
+///
+/// The os_log macros record the `__dso_handle` of the image they're compiled
+/// into, which for this crate's shim is whichever image links the crate.
+/// `image!` captures the caller's handle instead, along with format strings
+/// placed in the caller's image, since the system stores them as offsets from
+/// the handle. Every logging method honours it, including
+/// `with_source_location` and the timing methods.
+///
+/// ```no_run
+/// # use oslog::OsLog;
+/// let log = OsLog::new("com.example.test", "Plugin").image(oslog::image!());
+/// log.default("Attributed to the image this was compiled into");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Image {
+    pub(crate) dso: *const c_void,
+    pub(crate) public_format: *const c_char,
+    pub(crate) private_format: *const c_char,
+    pub(crate) location_format: *const c_char,
+    pub(crate) duration_format: *const c_char,
+}
+
+// Everything points at static data in a loaded image.
+unsafe impl Send for Image {}
+unsafe impl Sync for Image {}
+
+impl Image {
+    /// # Safety
+    ///
+    /// `dso` must be an image's `__dso_handle`, and the formats NUL
+    /// terminated copies of the ones `image!` defines, in that image's
+    /// `__oslogstring` section.
+    #[doc(hidden)]
+    pub const unsafe fn __new(
+        dso: *const c_void,
+        public_format: *const c_char,
+        private_format: *const c_char,
+        location_format: *const c_char,
+        duration_format: *const c_char,
+    ) -> Self {
+        Self {
+            dso,
+            public_format,
+            private_format,
+            location_format,
+            duration_format,
+        }
+    }
+
+    pub(crate) unsafe fn log(&self, log: os_log_t, level: Level, message: &CStr) {
+        wrapped_os_log_with_dso(
+            self.dso,
+            self.public_format,
+            log,
+            level as u8,
+            message.as_ptr(),
+        )
+    }
+
+    pub(crate) unsafe fn log_private(&self, log: os_log_t, level: Level, message: &CStr) {
+        wrapped_os_log_with_dso_private(
+            self.dso,
+            self.private_format,
+            log,
+            level as u8,
+            message.as_ptr(),
+        )
+    }
+
+    pub(crate) unsafe fn log_with_location(
+        &self,
+        log: os_log_t,
+        level: Level,
+        message: &CStr,
+        location: &SourceLocation,
+    ) {
+        wrapped_os_log_with_location_dso(
+            self.dso,
+            self.location_format,
+            log,
+            level as u8,
+            message.as_ptr(),
+            location.module.as_ptr(),
+            location.file.as_ptr(),
+            location.line,
+        )
+    }
+
+    pub(crate) unsafe fn log_with_duration(
+        &self,
+        log: os_log_t,
+        level: Level,
+        message: &CStr,
+        milliseconds: f64,
+    ) {
+        wrapped_os_log_with_duration_dso(
+            self.dso,
+            self.duration_format,
+            log,
+            level as u8,
+            message.as_ptr(),
+            milliseconds,
+        )
+    }
+}
+
+/// Captures the image the call is compiled into, for `OsLog::image`.
+#[macro_export]
+macro_rules! image {
+    () => {{
+        #[cfg_attr(
+            target_vendor = "apple",
+            link_section = "__TEXT,__oslogstring,cstring_literals"
+        )]
+        static PUBLIC_FORMAT: [u8; 11] = *b"%{public}s\0";
+        #[cfg_attr(
+            target_vendor = "apple",
+            link_section = "__TEXT,__oslogstring,cstring_literals"
+        )]
+        static PRIVATE_FORMAT: [u8; 12] = *b"%{private}s\0";
+        #[cfg_attr(
+            target_vendor = "apple",
+            link_section = "__TEXT,__oslogstring,cstring_literals"
+        )]
+        static LOCATION_FORMAT: [u8; 38] = *b"%{public}s [%{public}s %{public}s:%u]\0";
+        #[cfg_attr(
+            target_vendor = "apple",
+            link_section = "__TEXT,__oslogstring,cstring_literals"
+        )]
+        static DURATION_FORMAT: [u8; 25] = *b"%{public}s (took %.2fms)\0";
+
+        #[cfg(target_vendor = "apple")]
+        let dso = {
+            extern "C" {
+                static __dso_handle: u8;
+            }
+            unsafe { ::std::ptr::addr_of!(__dso_handle) as *const ::std::ffi::c_void }
+        };
+        #[cfg(not(target_vendor = "apple"))]
+        let dso = ::std::ptr::null();
+
+        unsafe {
+            $crate::Image::__new(
+                dso,
+                PUBLIC_FORMAT.as_ptr() as *const ::std::os::raw::c_char,
+                PRIVATE_FORMAT.as_ptr() as *const ::std::os::raw::c_char,
+                LOCATION_FORMAT.as_ptr() as *const ::std::os::raw::c_char,
+                DURATION_FORMAT.as_ptr() as *const ::std::os::raw::c_char,
+            )
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::OsLog;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_image() {
+        let image = image!();
+        let formats = unsafe {
+            (
+                CStr::from_ptr(image.public_format),
+                CStr::from_ptr(image.private_format),
+            )
+        };
+        assert_eq!(formats.0.to_str(), Ok("%{public}s"));
+        assert_eq!(formats.1.to_str(), Ok("%{private}s"));
+
+        let log = OsLog::new("com.example.oslog", "image").image(image);
+        log.default("Hello");
+        log.child("Child").default("Hello");
+    }
+}
@@ -0,0 +1,361 @@
+//! Readers and writers which show slow reads and writes in Instruments, for
+//! profiling file and socket I/O.
+
+use crate::signpost::mach_time;
+use crate::sys::*;
+use crate::{OsLog, SignpostId};
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+
+/// Calls faster than this aren't shown unless the threshold is changed.
+pub const DEFAULT_THRESHOLD: Duration = Duration::from_millis(1);
+
+/// Where calls are logged, and which are slow enough to be.
+struct IoLog {
+    log: &'static OsLog,
+    name: &'static str,
+    threshold: Duration,
+}
+
+impl IoLog {
+    fn new(log: &'static OsLog, name: &'static str) -> Self {
+        Self {
+            log,
+            name,
+            threshold: DEFAULT_THRESHOLD,
+        }
+    }
+
+    /// Emits an "IO" interval if the call took at least the threshold.
+    fn record(&self, mode: &str, start: Instant, bytes: usize) {
+        let end = Instant::now();
+        if end - start < self.threshold || !self.log.signposts_enabled() {
+            return;
+        }
+
+        let id = SignpostId::generate(self.log);
+        let (start, end) = (mach_time(start), mach_time(end));
+
+        self.log.emit_signpost(self.name, mode, |message| unsafe {
+            wrapped_os_signpost_io(
+                self.log.inner,
+                id.as_u64(),
+                message.as_ptr(),
+                start,
+                end,
+                bytes as u64,
+            )
+        });
+    }
+
+    fn time<T>(
+        &self,
+        mode: &str,
+        call: impl FnOnce() -> io::Result<T>,
+        bytes: impl Fn(&T) -> usize,
+    ) -> io::Result<T> {
+        let start = Instant::now();
+        let result = call();
+        if let Ok(value) = &result {
+            self.record(mode, start, bytes(value));
+        }
+        result
+    }
+}
+
+/// Wraps a reader and emits an "IO" signpost interval, with the number of
+/// bytes read as an integer argument, for each read which takes at least the
+/// threshold.
+///
+/// ```no_run
+/// use oslog::io::SignpostReader;
+/// use std::fs::File;
+/// use std::io::Read;
+///
+/// oslog::define_log!(IO, "com.example.test", "IO");
+///
+/// let mut reader = SignpostReader::new(File::open("data.bin")?, &IO, "data.bin");
+/// let mut contents = Vec::new();
+/// reader.read_to_end(&mut contents)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct SignpostReader<R> {
+    io_log: IoLog,
+    inner: R,
+}
+
+impl<R> SignpostReader<R> {
+    /// Reads are named `name` and logged to `log`.
+    pub fn new(inner: R, log: &'static OsLog, name: &'static str) -> Self {
+        Self {
+            io_log: IoLog::new(log, name),
+            inner,
+        }
+    }
+
+    pub fn threshold(mut self, threshold: Duration) -> Self {
+        self.io_log.threshold = threshold;
+        self
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for SignpostReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let inner = &mut self.inner;
+        self.io_log.time("read", || inner.read(buf), |read| *read)
+    }
+}
+
+/// Wraps a writer and emits an "IO" signpost interval, with the number of
+/// bytes written as an integer argument, for each write or flush which takes
+/// at least the threshold.
+pub struct SignpostWriter<W> {
+    io_log: IoLog,
+    inner: W,
+}
+
+impl<W> SignpostWriter<W> {
+    /// Writes are named `name` and logged to `log`.
+    pub fn new(inner: W, log: &'static OsLog, name: &'static str) -> Self {
+        Self {
+            io_log: IoLog::new(log, name),
+            inner,
+        }
+    }
+
+    pub fn threshold(mut self, threshold: Duration) -> Self {
+        self.io_log.threshold = threshold;
+        self
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for SignpostWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let inner = &mut self.inner;
+        self.io_log
+            .time("write", || inner.write(buf), |written| *written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let inner = &mut self.inner;
+        self.io_log.time("flush", || inner.flush(), |_| 0)
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod tokio_io {
+    use super::*;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+    /// When the pending operation was first polled, so its latency covers the
+    /// whole wait rather than the last poll.
+    fn poll_timed<T>(
+        io_log: &IoLog,
+        started: &mut Option<Instant>,
+        mode: &str,
+        poll: impl FnOnce() -> Poll<io::Result<T>>,
+        bytes: impl Fn(&T) -> usize,
+    ) -> Poll<io::Result<T>> {
+        let start = *started.get_or_insert_with(Instant::now);
+        let poll = poll();
+
+        if let Poll::Ready(result) = &poll {
+            *started = None;
+            if let Ok(value) = result {
+                io_log.record(mode, start, bytes(value));
+            }
+        }
+
+        poll
+    }
+
+    /// Like `SignpostReader`, for tokio's `AsyncRead`. Each read's latency is
+    /// measured from when it was first polled until it completed.
+    pub struct AsyncSignpostReader<R> {
+        io_log: IoLog,
+        started: Option<Instant>,
+        inner: R,
+    }
+
+    impl<R> AsyncSignpostReader<R> {
+        /// Reads are named `name` and logged to `log`.
+        pub fn new(inner: R, log: &'static OsLog, name: &'static str) -> Self {
+            Self {
+                io_log: IoLog::new(log, name),
+                started: None,
+                inner,
+            }
+        }
+
+        pub fn threshold(mut self, threshold: Duration) -> Self {
+            self.io_log.threshold = threshold;
+            self
+        }
+
+        pub fn into_inner(self) -> R {
+            self.inner
+        }
+    }
+
+    impl<R: AsyncRead + Unpin> AsyncRead for AsyncSignpostReader<R> {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            let this = self.get_mut();
+            let filled = buf.filled().len();
+            let inner = Pin::new(&mut this.inner);
+
+            let poll = poll_timed(
+                &this.io_log,
+                &mut this.started,
+                "read",
+                || {
+                    inner
+                        .poll_read(cx, buf)
+                        .map_ok(|()| buf.filled().len() - filled)
+                },
+                |read| *read,
+            );
+
+            poll.map_ok(|_| ())
+        }
+    }
+
+    /// Like `SignpostWriter`, for tokio's `AsyncWrite`. Each write's latency
+    /// is measured from when it was first polled until it completed.
+    pub struct AsyncSignpostWriter<W> {
+        io_log: IoLog,
+        started: Option<Instant>,
+        inner: W,
+    }
+
+    impl<W> AsyncSignpostWriter<W> {
+        /// Writes are named `name` and logged to `log`.
+        pub fn new(inner: W, log: &'static OsLog, name: &'static str) -> Self {
+            Self {
+                io_log: IoLog::new(log, name),
+                started: None,
+                inner,
+            }
+        }
+
+        pub fn threshold(mut self, threshold: Duration) -> Self {
+            self.io_log.threshold = threshold;
+            self
+        }
+
+        pub fn into_inner(self) -> W {
+            self.inner
+        }
+    }
+
+    impl<W: AsyncWrite + Unpin> AsyncWrite for AsyncSignpostWriter<W> {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            let inner = Pin::new(&mut this.inner);
+            poll_timed(
+                &this.io_log,
+                &mut this.started,
+                "write",
+                || inner.poll_write(cx, buf),
+                |written| *written,
+            )
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            let this = self.get_mut();
+            let inner = Pin::new(&mut this.inner);
+            poll_timed(
+                &this.io_log,
+                &mut this.started,
+                "flush",
+                || inner.poll_flush(cx),
+                |_| 0,
+            )
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+pub use tokio_io::{AsyncSignpostReader, AsyncSignpostWriter};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    crate::define_log!(IO, "com.example.oslog", "io");
+
+    #[test]
+    fn test_reader() {
+        let mut reader = SignpostReader::new(&b"Hello"[..], &IO, "test").threshold(Duration::ZERO);
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "Hello");
+    }
+
+    #[test]
+    fn test_writer() {
+        let mut writer = SignpostWriter::new(Vec::new(), &IO, "test").threshold(Duration::ZERO);
+        writer.write_all(b"Hello").unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.into_inner(), b"Hello");
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_async_reader() {
+        use std::pin::Pin;
+        use tokio::io::{AsyncRead, ReadBuf};
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let mut reader =
+            AsyncSignpostReader::new(&b"Hello"[..], &IO, "test").threshold(Duration::ZERO);
+        let mut bytes = [0; 8];
+        let mut buf = ReadBuf::new(&mut bytes);
+
+        runtime
+            .block_on(std::future::poll_fn(|cx| {
+                Pin::new(&mut reader).poll_read(cx, &mut buf)
+            }))
+            .unwrap();
+        assert_eq!(buf.filled(), b"Hello");
+    }
+}
@@ -0,0 +1,61 @@
+use crate::{Error, Level, OsLog};
+use serde::Serialize;
+
+fn format_json(label: &str, value: &impl Serialize) -> Result<String, Error> {
+    let json = serde_json::to_string(value).map_err(|e| Error::Serialize(e.to_string()))?;
+    Ok(format!("{}: {}", label, json))
+}
+
+impl OsLog {
+    /// Logs `value` as compact JSON after `label`, e.g. `request: {"id":1}`,
+    /// so it can be parsed back out of the log later. Newlines in strings are
+    /// escaped, so each value is on one line, and the truncation policy
+    /// applies as usual.
+    #[track_caller]
+    pub fn log_json(&self, level: Level, label: &str, value: &impl Serialize) -> Result<(), Error> {
+        self.try_with_level(level, &format_json(label, value)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Truncation;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    struct Request {
+        id: u32,
+        path: &'static str,
+    }
+
+    #[test]
+    fn test_format_json() {
+        let request = Request {
+            id: 1,
+            path: "/multi\nline",
+        };
+        assert_eq!(
+            format_json("request", &request),
+            Ok(r#"request: {"id":1,"path":"/multi\nline"}"#.to_string())
+        );
+
+        let mut map = BTreeMap::new();
+        map.insert(vec![1], 2);
+        assert!(matches!(format_json("map", &map), Err(Error::Serialize(_))));
+    }
+
+    #[test]
+    fn test_log_json() {
+        let request = Request { id: 1, path: "/" };
+
+        let log = OsLog::new("com.example.oslog", "json");
+        assert_eq!(log.log_json(Level::Info, "request", &request), Ok(()));
+
+        let log = log.truncation(Truncation::Error, 8);
+        assert!(matches!(
+            log.log_json(Level::Info, "request", &request),
+            Err(Error::MessageTooLong { .. })
+        ));
+    }
+}
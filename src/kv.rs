@@ -0,0 +1,71 @@
+use crate::event::format_value;
+use log::kv::{self, Key, Value, VisitSource};
+use log::Record;
+use std::fmt::Write;
+
+struct Renderer(String);
+
+impl<'kvs> VisitSource<'kvs> for Renderer {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        let _ = write!(self.0, " {}={}", key, render_value(&value));
+        Ok(())
+    }
+}
+
+#[cfg(feature = "kv-serde")]
+fn is_primitive(value: &Value) -> bool {
+    value.to_borrowed_str().is_some()
+        || value.to_bool().is_some()
+        || value.to_char().is_some()
+        || value.to_i64().is_some()
+        || value.to_u64().is_some()
+        || value.to_f64().is_some()
+}
+
+/// Structured values are rendered as compact JSON with the `kv-serde`
+/// feature, which isn't quoted since it delimits itself.
+fn render_value(value: &Value) -> String {
+    #[cfg(feature = "kv-serde")]
+    if !is_primitive(value) {
+        if let Ok(json) = serde_json::to_string(value) {
+            return json;
+        }
+    }
+
+    format_value(&value.to_string())
+}
+
+/// Renders the record's key-values as " key=value" pairs, in order.
+pub(crate) fn render(record: &Record) -> String {
+    let mut renderer = Renderer(String::new());
+    let _ = record.key_values().visit(&mut renderer);
+    renderer.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let pairs = [
+            ("user", Value::from("jane doe")),
+            ("attempt", Value::from(3)),
+            ("ok", Value::from(true)),
+        ];
+        let record = Record::builder().key_values(&pairs).build();
+        assert_eq!(render(&record), " user=\"jane doe\" attempt=3 ok=true");
+
+        let record = Record::builder().build();
+        assert_eq!(render(&record), "");
+    }
+
+    #[cfg(feature = "kv-serde")]
+    #[test]
+    fn test_render_serde() {
+        let tags = vec!["a", "b c"];
+        let pairs = [("tags", Value::from_serde(&tags))];
+        let record = Record::builder().key_values(&pairs).build();
+        assert_eq!(render(&record), " tags=[\"a\",\"b c\"]");
+    }
+}
@@ -1,21 +1,156 @@
+mod build_info;
+mod builder;
+mod capture;
+pub mod context;
+mod define;
+mod error;
+mod event;
+mod ext;
+mod fatal;
+mod image;
+mod location;
+mod message;
+mod metadata;
+mod mock;
+mod name;
+mod nul_bytes;
+mod panic;
+mod router;
+mod signpost;
+mod signposter;
+mod stack;
 mod sys;
+mod timing;
+mod truncation;
+mod utf8;
+mod writer;
 
 #[cfg(feature = "logger")]
 mod logger;
 
 #[cfg(feature = "logger")]
-pub use logger::OsLogger;
+mod session;
 
+#[cfg(feature = "logger")]
+mod stats;
+
+#[cfg(feature = "serde")]
+mod json;
+
+#[cfg(feature = "kv")]
+mod kv;
+
+#[cfg(feature = "capi")]
+pub mod capi;
+
+#[cfg(feature = "capi")]
+pub mod handle;
+
+#[cfg(all(feature = "objc2", target_vendor = "apple"))]
+mod objc2_interop;
+
+#[cfg(feature = "store")]
+pub mod store;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[cfg(feature = "test-util")]
+pub mod test_util;
+
+pub mod activity;
+pub mod alloc;
+pub mod bench;
+pub mod breadcrumb;
+pub mod io;
+pub mod lock;
+pub mod profile;
+pub mod sink;
+pub mod thread;
+
+#[cfg(any(feature = "anyhow", feature = "eyre"))]
+pub mod report;
+
+#[cfg(feature = "opentelemetry")]
+pub mod otel;
+
+#[cfg(feature = "metrics")]
+pub mod recorder;
+
+#[cfg(feature = "tokio")]
+pub mod runtime;
+
+#[cfg(feature = "tower")]
+pub mod tower;
+
+pub use breadcrumb::breadcrumb;
+pub use build_info::__log_build_info;
+pub use builder::{OsLogBuilder, Privacy};
+pub use capture::{capture_std_streams, forward_child_output, spawn_forwarding, ForwardedOutput};
+pub use define::LazyLog;
+pub use error::Error;
+pub use event::Event;
+pub use ext::{OptionExt, ResultExt};
+pub use image::Image;
+pub use location::SourceLocation;
+pub use message::LogMessage;
+pub use metadata::{Metadata, MetadataValue};
+pub use mock::MockLog;
+pub use name::{
+    __assert_valid_names, is_reserved_category, validate_category, validate_subsystem, Category,
+    NameError, Subsystem, MAX_NAME_LENGTH, RESERVED_CATEGORIES,
+};
+pub use nul_bytes::NulBytes;
+pub use panic::install_panic_hook;
+pub use router::LevelRouter;
+pub use signpost::{__enclosing_function, SignpostId, SignpostInterval};
+pub use signposter::{IntervalState, Signposter};
+pub use truncation::Truncation;
+pub use utf8::InvalidUtf8;
+pub use writer::OsLogWriter;
+
+#[cfg(feature = "logger")]
+pub use logger::{OsLogger, OsLoggerHandle, FAULT_TARGET};
+
+#[cfg(feature = "logger")]
+pub use session::SESSION_CATEGORY;
+
+#[cfg(feature = "logger")]
+pub use stats::{LogStats, STATS_CATEGORY};
+
+#[cfg(feature = "logger")]
+#[doc(hidden)]
+pub use log as __log;
+
+use crate::message::StackBuffer;
 use crate::sys::*;
-use std::ffi::{c_void, CString};
+#[cfg(feature = "backtrace")]
+use std::backtrace::Backtrace;
+use std::borrow::Cow;
+use std::ffi::{c_void, CStr, CString, OsStr};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::os::unix::ffi::OsStrExt;
+use std::panic::Location;
+use std::path::Path;
+use std::sync::Arc;
+
+/// The maximum length in bytes of each entry a fault and its backtrace are
+/// split over, as are messages logged with `error_with_backtrace`.
+pub const BACKTRACE_CHUNK_LENGTH: usize = 1024;
 
 #[inline]
-fn to_cstr(message: &str) -> CString {
-    let fixed = message.replace('\0', "(null)");
-    CString::new(fixed).unwrap()
+fn to_cstr(message: &str, nul_bytes: NulBytes) -> Result<CString, Error> {
+    match CString::new(message) {
+        Ok(message) => Ok(message),
+        Err(e) => nul_bytes.apply(message, e.nul_position()),
+    }
 }
 
 #[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum Level {
     Debug = OS_LOG_TYPE_DEBUG,
     Info = OS_LOG_TYPE_INFO,
@@ -24,6 +159,32 @@ pub enum Level {
     Fault = OS_LOG_TYPE_FAULT,
 }
 
+impl Level {
+    /// Orders levels from least to most severe, which their values don't.
+    fn severity(self) -> u8 {
+        match self {
+            Self::Debug => 0,
+            Self::Info => 1,
+            Self::Default => 2,
+            Self::Error => 3,
+            Self::Fault => 4,
+        }
+    }
+
+    /// Converts one of the OS_LOG_TYPE_* values.
+    #[cfg(feature = "capi")]
+    pub(crate) fn from_raw(level: u8) -> Option<Self> {
+        match level {
+            OS_LOG_TYPE_DEBUG => Some(Self::Debug),
+            OS_LOG_TYPE_INFO => Some(Self::Info),
+            OS_LOG_TYPE_DEFAULT => Some(Self::Default),
+            OS_LOG_TYPE_ERROR => Some(Self::Error),
+            OS_LOG_TYPE_FAULT => Some(Self::Fault),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(feature = "logger")]
 impl From<log::Level> for Level {
     fn from(other: log::Level) -> Self {
@@ -37,8 +198,51 @@ impl From<log::Level> for Level {
     }
 }
 
+/// The logging methods shared by `OsLog` and `MockLog`, so code can accept
+/// either.
+pub trait OsLogLike {
+    fn with_level(&self, level: Level, message: &str);
+
+    fn level_is_enabled(&self, level: Level) -> bool;
+
+    fn debug(&self, message: &str) {
+        self.with_level(Level::Debug, message);
+    }
+
+    fn info(&self, message: &str) {
+        self.with_level(Level::Info, message);
+    }
+
+    fn default(&self, message: &str) {
+        self.with_level(Level::Default, message);
+    }
+
+    fn error(&self, message: &str) {
+        self.with_level(Level::Error, message);
+    }
+
+    fn fault(&self, message: &str) {
+        self.with_level(Level::Fault, message);
+    }
+
+    /// Logs `error` and each of its sources as a single error level entry.
+    fn error_chain(&self, error: &dyn std::error::Error) {
+        self.error(&ext::format_error_chain(error));
+    }
+}
+
 pub struct OsLog {
     inner: os_log_t,
+    subsystem: String,
+    category: String,
+    truncation: Option<(Truncation, usize)>,
+    nul_bytes: NulBytes,
+    invalid_utf8: InvalidUtf8,
+    caller_location: bool,
+    metadata: Metadata,
+    privacy: Privacy,
+    default_level: Option<Level>,
+    image: Option<Image>,
 }
 
 unsafe impl Send for OsLog {}
@@ -47,23 +251,97 @@ unsafe impl Sync for OsLog {}
 impl Drop for OsLog {
     fn drop(&mut self) {
         unsafe {
-            if self.inner != wrapped_get_default_log() {
+            if self.inner != wrapped_get_default_log() && !self.is_disabled() {
                 os_release(self.inner as *mut c_void);
             }
         }
     }
 }
 
+/// Logs are equal when they log to the same subsystem and category,
+/// regardless of their other settings.
+impl PartialEq for OsLog {
+    fn eq(&self, other: &Self) -> bool {
+        self.subsystem == other.subsystem && self.category == other.category
+    }
+}
+
+impl Eq for OsLog {}
+
+impl Hash for OsLog {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.subsystem.hash(state);
+        self.category.hash(state);
+    }
+}
+
 impl OsLog {
     pub fn new(subsystem: &str, category: &str) -> Self {
-        let subsystem = to_cstr(subsystem);
-        let category = to_cstr(category);
+        let subsystem_cstr = to_cstr(subsystem, NulBytes::default()).unwrap();
+        let category_cstr = to_cstr(category, NulBytes::default()).unwrap();
 
-        let inner = unsafe { os_log_create(subsystem.as_ptr(), category.as_ptr()) };
+        let inner = unsafe { os_log_create(subsystem_cstr.as_ptr(), category_cstr.as_ptr()) };
 
         assert!(!inner.is_null(), "Unexpected null value from os_log_create");
 
-        Self { inner }
+        Self {
+            inner,
+            subsystem: subsystem.to_string(),
+            category: category.to_string(),
+            truncation: None,
+            nul_bytes: NulBytes::default(),
+            invalid_utf8: InvalidUtf8::default(),
+            caller_location: false,
+            metadata: Metadata::new(),
+            privacy: Privacy::default(),
+            default_level: None,
+            image: None,
+        }
+    }
+
+    /// Like `new`, but returns an error if either name is invalid, rather
+    /// than having NUL bytes replaced or the name truncated by the system.
+    /// Reserved categories are rejected too, since they change how the
+    /// system treats the log; use their own constructors instead.
+    pub fn try_new(subsystem: &str, category: &str) -> Result<Self, Error> {
+        validate_subsystem(subsystem).map_err(Error::InvalidSubsystem)?;
+        validate_category(category).map_err(Error::InvalidCategory)?;
+        if is_reserved_category(category) {
+            return Err(Error::ReservedCategory(category.to_string()));
+        }
+        Ok(Self::new(subsystem, category))
+    }
+
+    /// Like `new`, but with names which have already been validated, and
+    /// can't be passed in the wrong order.
+    ///
+    /// ```no_run
+    /// use oslog::{Category, OsLog, Subsystem};
+    ///
+    /// const APP: Subsystem = Subsystem::new("com.example.test");
+    ///
+    /// let log = OsLog::from_names(APP, Category::new("Network"));
+    /// let signposts = OsLog::from_names(APP, Category::POINTS_OF_INTEREST);
+    /// ```
+    pub fn from_names(subsystem: Subsystem, category: Category) -> Self {
+        Self::new(subsystem.as_str(), category.as_str())
+    }
+
+    /// Creates a log whose signposts are shown in Instruments' Points of
+    /// Interest track.
+    pub fn points_of_interest(subsystem: &str) -> Self {
+        Self::new(subsystem, Category::POINTS_OF_INTEREST.as_str())
+    }
+
+    /// Creates a log whose signposts are only emitted while Instruments is
+    /// recording them.
+    pub fn dynamic_tracing(subsystem: &str) -> Self {
+        Self::new(subsystem, Category::DYNAMIC_TRACING.as_str())
+    }
+
+    /// Like `dynamic_tracing`, but each signpost also records a backtrace.
+    pub fn dynamic_stack_tracing(subsystem: &str) -> Self {
+        Self::new(subsystem, Category::DYNAMIC_STACK_TRACING.as_str())
     }
 
     pub fn global() -> Self {
@@ -71,48 +349,682 @@ impl OsLog {
 
         assert!(!inner.is_null(), "Unexpected null value for OS_DEFAULT_LOG");
 
-        Self { inner }
+        Self {
+            inner,
+            subsystem: String::new(),
+            category: String::new(),
+            truncation: None,
+            nul_bytes: NulBytes::default(),
+            invalid_utf8: InvalidUtf8::default(),
+            caller_location: false,
+            metadata: Metadata::new(),
+            privacy: Privacy::default(),
+            default_level: None,
+            image: None,
+        }
+    }
+
+    /// A log which discards everything, for code which takes a log when the
+    /// caller doesn't want its messages. The OS returns early from every
+    /// call, and the memory sink doesn't record it.
+    pub fn disabled() -> Self {
+        Self {
+            inner: unsafe { wrapped_get_disabled_log() },
+            subsystem: String::new(),
+            category: String::new(),
+            truncation: None,
+            nul_bytes: NulBytes::default(),
+            invalid_utf8: InvalidUtf8::default(),
+            caller_location: false,
+            metadata: Metadata::new(),
+            privacy: Privacy::default(),
+            default_level: None,
+            image: None,
+        }
+    }
+
+    /// Wraps an `os_log_t` created in Swift, so both languages log through
+    /// the same object. The handle is retained, and released when the
+    /// returned log is dropped, so Swift can pass it unretained and keep its
+    /// own reference:
+    ///
+    /// ```swift
+    /// let log = OSLog(subsystem: "com.example.test", category: "Sync")
+    /// rust_use_log(Unmanaged.passUnretained(log).toOpaque())
+    /// ```
+    ///
+    /// A `Logger` doesn't expose its `OSLog`, so create the `Logger` from
+    /// the `OSLog` instead. The OS doesn't expose a log's names either, so
+    /// `subsystem` and `category` should be the ones it was created with;
+    /// they're used for comparisons and child categories.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a valid `os_log_t`, e.g. `OS_LOG_DEFAULT` or one
+    /// returned by `os_log_create`.
+    pub unsafe fn from_swift_handle(handle: *mut c_void, subsystem: &str, category: &str) -> Self {
+        assert!(!handle.is_null(), "Unexpected null Swift log handle");
+
+        let inner = handle as os_log_t;
+        if inner != wrapped_get_default_log() && inner != wrapped_get_disabled_log() {
+            os_retain(handle);
+        }
+
+        Self {
+            inner,
+            subsystem: subsystem.to_string(),
+            category: category.to_string(),
+            truncation: None,
+            nul_bytes: NulBytes::default(),
+            invalid_utf8: InvalidUtf8::default(),
+            caller_location: false,
+            metadata: Metadata::new(),
+            privacy: Privacy::default(),
+            default_level: None,
+            image: None,
+        }
+    }
+
+    fn is_disabled(&self) -> bool {
+        self.inner == unsafe { wrapped_get_disabled_log() }
+    }
+
+    /// Creates a log in the same subsystem, with the same settings, whose
+    /// category is `name` nested under this log's, e.g. "Sync.Network".
+    ///
+    /// ```no_run
+    /// # use oslog::OsLog;
+    /// let sync = OsLog::new("com.example.test", "Sync");
+    /// let network = sync.child("Network");
+    /// let retries = network.child("Retries"); // "Sync.Network.Retries"
+    /// ```
+    pub fn child(&self, name: &str) -> Self {
+        if self.category.is_empty() {
+            return self.sibling(name);
+        }
+
+        self.sibling(&format!("{}.{}", self.category, name))
+    }
+
+    fn below_default_level(&self, level: Level) -> bool {
+        self.default_level
+            .is_some_and(|default_level| level.severity() < default_level.severity())
+    }
+
+    /// Creates a log for `category` in the same subsystem, with the same
+    /// settings.
+    pub(crate) fn sibling(&self, category: &str) -> Self {
+        let mut log = Self::new(&self.subsystem, category);
+        log.truncation = self.truncation;
+        log.nul_bytes = self.nul_bytes;
+        log.invalid_utf8 = self.invalid_utf8;
+        log.caller_location = self.caller_location;
+        log.metadata = self.metadata.clone();
+        log.privacy = self.privacy;
+        log.default_level = self.default_level;
+        log.image = self.image;
+        log
+    }
+
+    /// Messages longer than `max_length` bytes will be handled according to
+    /// `policy`. By default messages are passed to the OS untouched.
+    pub fn truncation(mut self, policy: Truncation, max_length: usize) -> Self {
+        self.truncation = Some((policy, max_length));
+        self
+    }
+
+    /// NUL bytes in messages will be handled according to `policy`. By
+    /// default they're replaced with "(null)".
+    pub fn nul_bytes(mut self, policy: NulBytes) -> Self {
+        self.nul_bytes = policy;
+        self
+    }
+
+    /// Invalid UTF-8 passed to `log_bytes`, `log_os_str` and `log_path` will
+    /// be handled according to `policy`. By default it's replaced lossily.
+    pub fn invalid_utf8(mut self, policy: InvalidUtf8) -> Self {
+        self.invalid_utf8 = policy;
+        self
+    }
+
+    /// Appends the file and line the logging method was called from to each
+    /// message, e.g. "Hello (src/main.rs:12)".
+    pub fn caller_location(mut self, enabled: bool) -> Self {
+        self.caller_location = enabled;
+        self
+    }
+
+    /// Attributes messages to `image` in Console, rather than to the image
+    /// this crate is linked into.
+    pub fn image(mut self, image: Image) -> Self {
+        self.image = Some(image);
+        self
+    }
+
+    #[track_caller]
+    fn locate<'a>(&self, message: &'a str) -> Cow<'a, str> {
+        if context::is_empty() {
+            self.locate_with(message, &self.metadata)
+        } else {
+            self.locate_with(message, &self.merged(&Metadata::new()))
+        }
+    }
+
+    #[track_caller]
+    fn locate_with<'a>(&self, message: &'a str, metadata: &Metadata) -> Cow<'a, str> {
+        let mut message = Cow::Borrowed(message);
+
+        if !metadata.is_empty() {
+            message = Cow::Owned(format!("{} {}", message, metadata::render(metadata)));
+        }
+
+        if self.caller_location {
+            let location = Location::caller();
+            message = Cow::Owned(format!(
+                "{} ({}:{})",
+                message,
+                location.file(),
+                location.line()
+            ));
+        }
+
+        message
+    }
+
+    /// Whether messages are logged as they were passed, without anything
+    /// appended.
+    fn is_undecorated(&self) -> bool {
+        self.metadata.is_empty() && !self.caller_location && context::is_empty()
+    }
+
+    /// Converts, decorates and emits `message`, except for C strings which
+    /// need nothing added or truncated, which are emitted as they are.
+    #[track_caller]
+    fn try_emit_message(
+        &self,
+        level: Level,
+        message: LogMessage,
+        emit: impl Fn(&CStr),
+    ) -> Result<(), Error> {
+        if cfg!(feature = "off") || self.below_default_level(level) {
+            return Ok(());
+        }
+
+        let mut buffer = StackBuffer::new();
+        let message = message.resolve(&mut buffer);
+
+        if let Some(message) = message.as_cstr() {
+            let fits = self.truncation.map_or(true, |(_, max_length)| {
+                message.to_bytes().len() <= max_length
+            });
+
+            if fits && self.is_undecorated() {
+                self.emit_recorded(level, message, emit);
+                return Ok(());
+            }
+        }
+
+        let message = message.to_str();
+        let message = self.locate(&message);
+        self.try_emit(level, &message, emit)
+    }
+
+    /// Records `message` in the memory sink if it's enabled, otherwise emits
+    /// it.
+    fn emit_recorded(&self, level: Level, message: &CStr, emit: impl Fn(&CStr)) {
+        if self.is_disabled() {
+            return;
+        }
+
+        let recorded = sink::record(|| sink::MemoryEntry {
+            subsystem: self.subsystem.clone(),
+            category: self.category.clone(),
+            level,
+            message: message.to_string_lossy().into_owned(),
+        });
+
+        if !recorded {
+            profile::measure("os_log", || match self.privacy {
+                Privacy::Public => emit(message),
+                Privacy::Private => unsafe {
+                    match &self.image {
+                        Some(image) => image.log_private(self.inner, level, message),
+                        None => wrapped_os_log_with_type_private(
+                            self.inner,
+                            level as u8,
+                            message.as_ptr(),
+                        ),
+                    }
+                },
+            });
+        }
+    }
+
+    /// Calls `emit`, unless messages are attributed to another image.
+    unsafe fn emit_public(&self, level: Level, message: &CStr, emit: impl FnOnce()) {
+        match &self.image {
+            Some(image) => image.log(self.inner, level, message),
+            None => emit(),
+        }
+    }
+
+    fn try_emit(&self, level: Level, message: &str, emit: impl Fn(&CStr)) -> Result<(), Error> {
+        if cfg!(feature = "off") || self.below_default_level(level) {
+            return Ok(());
+        }
+
+        let emit = |message: &CStr| self.emit_recorded(level, message, &emit);
+
+        // NUL bytes are dealt with first, since replacing them changes the
+        // length.
+        let message = to_cstr(message, self.nul_bytes)?;
+
+        match self.truncation {
+            Some((policy, max_length)) if message.as_bytes().len() > max_length => {
+                // Made from `str`s, so it's still UTF-8.
+                let message = String::from_utf8_lossy(message.as_bytes());
+
+                // Convert every part up front so nothing is logged if any of
+                // them is rejected.
+                let parts = policy
+                    .apply(&message, max_length)?
+                    .iter()
+                    .map(|part| to_cstr(part, self.nul_bytes))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                for part in parts {
+                    emit(&part);
+                }
+            }
+            _ => emit(&message),
+        }
+
+        Ok(())
+    }
+
+    /// Messages rejected by the truncation or NUL byte policies are dropped,
+    /// use `try_with_level` to find out when that happens.
+    ///
+    /// Like the other logging methods, `message` can be a `&str`, a `&CStr`
+    /// or `format_args!`.
+    ///
+    /// ```no_run
+    /// # use oslog::{Level, OsLog};
+    /// let log = OsLog::new("com.example.test", "Parsing");
+    /// log.with_level(Level::Info, "Parsing");
+    /// log.with_level(Level::Info, oslog::cstr!("Parsing"));
+    /// log.with_level(Level::Info, format_args!("Parsed {} lines", 12));
+    /// ```
+    #[track_caller]
+    pub fn with_level<'a>(&self, level: Level, message: impl Into<LogMessage<'a>>) {
+        let _ = self.try_with_level(level, message);
+    }
+
+    #[track_caller]
+    pub fn try_with_level<'a>(
+        &self,
+        level: Level,
+        message: impl Into<LogMessage<'a>>,
+    ) -> Result<(), Error> {
+        self.try_emit_message(level, message.into(), |message| unsafe {
+            self.emit_public(level, message, || {
+                wrapped_os_log_with_type(self.inner, level as u8, message.as_ptr())
+            })
+        })
+    }
+
+    fn try_with_level_unlocated(&self, level: Level, message: &str) -> Result<(), Error> {
+        self.try_emit(level, message, |message| unsafe {
+            self.emit_public(level, message, || {
+                wrapped_os_log_with_type(self.inner, level as u8, message.as_ptr())
+            })
+        })
+    }
+
+    #[track_caller]
+    pub fn debug<'a>(&self, message: impl Into<LogMessage<'a>>) {
+        let _ = self.try_emit_message(Level::Debug, message.into(), |message| unsafe {
+            self.emit_public(Level::Debug, message, || {
+                wrapped_os_log_debug(self.inner, message.as_ptr())
+            })
+        });
     }
 
-    pub fn with_level(&self, level: Level, message: &str) {
-        let message = to_cstr(message);
-        unsafe { wrapped_os_log_with_type(self.inner, level as u8, message.as_ptr()) }
+    #[track_caller]
+    pub fn info<'a>(&self, message: impl Into<LogMessage<'a>>) {
+        let _ = self.try_emit_message(Level::Info, message.into(), |message| unsafe {
+            self.emit_public(Level::Info, message, || {
+                wrapped_os_log_info(self.inner, message.as_ptr())
+            })
+        });
     }
 
-    pub fn debug(&self, message: &str) {
-        let message = to_cstr(message);
-        unsafe { wrapped_os_log_debug(self.inner, message.as_ptr()) }
+    #[track_caller]
+    pub fn default<'a>(&self, message: impl Into<LogMessage<'a>>) {
+        let _ = self.try_emit_message(Level::Default, message.into(), |message| unsafe {
+            self.emit_public(Level::Default, message, || {
+                wrapped_os_log_default(self.inner, message.as_ptr())
+            })
+        });
     }
 
-    pub fn info(&self, message: &str) {
-        let message = to_cstr(message);
-        unsafe { wrapped_os_log_info(self.inner, message.as_ptr()) }
+    #[track_caller]
+    pub fn error<'a>(&self, message: impl Into<LogMessage<'a>>) {
+        let _ = self.try_emit_message(Level::Error, message.into(), |message| unsafe {
+            self.emit_public(Level::Error, message, || {
+                wrapped_os_log_error(self.inner, message.as_ptr())
+            })
+        });
     }
 
-    pub fn default(&self, message: &str) {
-        let message = to_cstr(message);
-        unsafe { wrapped_os_log_default(self.inner, message.as_ptr()) }
+    /// Logs formatted arguments without the intermediate `String` and
+    /// `CString` of `with_level(level, &format!(...))`. They're written to a
+    /// stack buffer, or a buffer reused by the thread if they're too long,
+    /// and NUL terminated in place.
+    ///
+    /// ```no_run
+    /// # use oslog::{Level, OsLog};
+    /// let log = OsLog::new("com.example.test", "Parsing");
+    /// log.log_fmt(Level::Info, format_args!("Parsed {} lines", 12));
+    /// ```
+    #[track_caller]
+    pub fn log_fmt(&self, level: Level, args: fmt::Arguments) {
+        self.with_level(level, args);
     }
 
-    pub fn error(&self, message: &str) {
-        let message = to_cstr(message);
-        unsafe { wrapped_os_log_error(self.inner, message.as_ptr()) }
+    /// With the `backtrace` feature enabled the current backtrace is
+    /// appended, and the result is split over as many entries as needed.
+    #[track_caller]
+    pub fn fault<'a>(&self, message: impl Into<LogMessage<'a>>) {
+        #[cfg(feature = "backtrace")]
+        {
+            let mut buffer = StackBuffer::new();
+            let message = message.into().resolve(&mut buffer);
+            let message = message.to_str();
+            let message = self.locate(&message);
+            let message = format!("{}\n{}", message, Backtrace::force_capture());
+            let parts = Truncation::Chunk
+                .apply(&message, BACKTRACE_CHUNK_LENGTH)
+                .unwrap_or_default();
+
+            for part in parts {
+                let _ = self.try_emit(Level::Fault, &part, |part| unsafe {
+                    self.emit_public(Level::Fault, part, || {
+                        wrapped_os_log_fault(self.inner, part.as_ptr())
+                    })
+                });
+            }
+        }
+
+        #[cfg(not(feature = "backtrace"))]
+        let _ = self.try_emit_message(Level::Fault, message.into(), |message| unsafe {
+            self.emit_public(Level::Fault, message, || {
+                wrapped_os_log_fault(self.inner, message.as_ptr())
+            })
+        });
+    }
+
+    /// Only calls `message` if `level` is enabled, so messages the system
+    /// would discard are never formatted.
+    #[track_caller]
+    pub fn with_level_lazy(&self, level: Level, message: impl FnOnce() -> String) {
+        if self.level_is_enabled(level) {
+            self.with_level(level, &message());
+        }
+    }
+
+    #[track_caller]
+    pub fn debug_lazy(&self, message: impl FnOnce() -> String) {
+        if self.debug_enabled() {
+            self.debug(&message());
+        }
+    }
+
+    #[track_caller]
+    pub fn info_lazy(&self, message: impl FnOnce() -> String) {
+        if self.info_enabled() {
+            self.info(&message());
+        }
+    }
+
+    #[track_caller]
+    pub fn default_lazy(&self, message: impl FnOnce() -> String) {
+        if self.level_is_enabled(Level::Default) {
+            self.default(&message());
+        }
+    }
+
+    #[track_caller]
+    pub fn error_lazy(&self, message: impl FnOnce() -> String) {
+        if self.level_is_enabled(Level::Error) {
+            self.error(&message());
+        }
     }
 
-    pub fn fault(&self, message: &str) {
-        let message = to_cstr(message);
+    #[track_caller]
+    pub fn fault_lazy(&self, message: impl FnOnce() -> String) {
+        if self.level_is_enabled(Level::Fault) {
+            self.fault(&message());
+        }
+    }
+
+    /// Logs `message` with `location` passed as separate os_log arguments.
+    /// Usually called through `log_with_location!`.
+    pub fn with_source_location(&self, level: Level, message: &str, location: SourceLocation) {
+        let _ = self.try_emit(level, message, |message| unsafe {
+            match &self.image {
+                Some(image) => image.log_with_location(self.inner, level, message, &location),
+                None => wrapped_os_log_with_location(
+                    self.inner,
+                    level as u8,
+                    message.as_ptr(),
+                    location.module.as_ptr(),
+                    location.file.as_ptr(),
+                    location.line,
+                ),
+            }
+        });
+    }
+
+    /// Logs `message` at the fault level without allocating or taking any
+    /// locks, so it can be called from signal handlers as a last resort. The
+    /// truncation, NUL byte and memory sink settings are ignored, and no
+    /// backtrace is attached.
+    ///
+    /// Apple doesn't document os_log as async-signal-safe, but it's designed
+    /// to be usable while crashing.
+    pub fn fault_signal_safe(&self, message: &CStr) {
         unsafe { wrapped_os_log_fault(self.inner, message.as_ptr()) }
     }
 
+    /// Logs `error` and each of its sources as a single error level entry.
+    #[track_caller]
+    pub fn error_chain(&self, error: &dyn std::error::Error) {
+        self.error(&ext::format_error_chain(error));
+    }
+
+    /// Logs an error the program anticipates and handles, e.g. a missing
+    /// file, at the error level, tagged "[expected] ".
+    #[track_caller]
+    pub fn expected_error(&self, message: &str) {
+        self.error(format_args!("[expected] {}", message));
+    }
+
+    /// Logs an error that indicates a bug or broken environment at the
+    /// fault level, tagged "[unexpected] ". Faults are costly to collect,
+    /// so anything anticipated should use `expected_error` instead.
+    #[track_caller]
+    pub fn unexpected_error(&self, message: &str) {
+        self.fault(format_args!("[unexpected] {}", message));
+    }
+
+    #[track_caller]
+    pub fn log_bytes(&self, level: Level, message: &[u8]) {
+        self.with_level(level, &self.invalid_utf8.decode(message));
+    }
+
+    #[track_caller]
+    pub fn log_os_str(&self, level: Level, message: &OsStr) {
+        self.log_bytes(level, message.as_bytes());
+    }
+
+    #[track_caller]
+    pub fn log_path(&self, level: Level, path: &Path) {
+        self.log_os_str(level, path.as_os_str());
+    }
+
+    /// Always true while the memory sink or stderr output is enabled, unless
+    /// the log is `disabled` or `level` is below its default level, and
+    /// always false with the `off` feature.
     pub fn level_is_enabled(&self, level: Level) -> bool {
+        if cfg!(feature = "off") || self.below_default_level(level) {
+            return false;
+        }
+
+        if (sink::is_memory_enabled() || sink::is_stderr_enabled()) && !self.is_disabled() {
+            return true;
+        }
+
         unsafe { os_log_type_enabled(self.inner, level as u8) }
     }
+
+    /// Mirrors `os_log_debug_enabled`.
+    pub fn debug_enabled(&self) -> bool {
+        self.level_is_enabled(Level::Debug)
+    }
+
+    /// Mirrors `os_log_info_enabled`.
+    pub fn info_enabled(&self) -> bool {
+        self.level_is_enabled(Level::Info)
+    }
+}
+
+macro_rules! impl_os_log_like_for_pointers {
+    ($($pointer:ty),*) => {
+        $(
+            impl<T: OsLogLike + ?Sized> OsLogLike for $pointer {
+                #[track_caller]
+                fn with_level(&self, level: Level, message: &str) {
+                    (**self).with_level(level, message);
+                }
+
+                fn level_is_enabled(&self, level: Level) -> bool {
+                    (**self).level_is_enabled(level)
+                }
+
+                #[track_caller]
+                fn debug(&self, message: &str) {
+                    (**self).debug(message);
+                }
+
+                #[track_caller]
+                fn info(&self, message: &str) {
+                    (**self).info(message);
+                }
+
+                #[track_caller]
+                fn default(&self, message: &str) {
+                    (**self).default(message);
+                }
+
+                #[track_caller]
+                fn error(&self, message: &str) {
+                    (**self).error(message);
+                }
+
+                #[track_caller]
+                fn fault(&self, message: &str) {
+                    (**self).fault(message);
+                }
+            }
+        )*
+    };
+}
+
+impl_os_log_like_for_pointers!(&T, Box<T>, Arc<T>);
+
+impl OsLogLike for OsLog {
+    #[track_caller]
+    fn with_level(&self, level: Level, message: &str) {
+        OsLog::with_level(self, level, message);
+    }
+
+    fn level_is_enabled(&self, level: Level) -> bool {
+        OsLog::level_is_enabled(self, level)
+    }
+
+    #[track_caller]
+    fn debug(&self, message: &str) {
+        OsLog::debug(self, message);
+    }
+
+    #[track_caller]
+    fn info(&self, message: &str) {
+        OsLog::info(self, message);
+    }
+
+    #[track_caller]
+    fn default(&self, message: &str) {
+        OsLog::default(self, message);
+    }
+
+    #[track_caller]
+    fn error(&self, message: &str) {
+        OsLog::error(self, message);
+    }
+
+    #[track_caller]
+    fn fault(&self, message: &str) {
+        OsLog::fault(self, message);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[cfg(feature = "off")]
+    #[test]
+    fn test_off() {
+        let log = OsLog::new("com.example.oslog", "off");
+        assert!(!log.level_is_enabled(Level::Fault));
+        assert!(!log.signposts_enabled());
+    }
+
+    #[test]
+    fn test_child() {
+        let log = OsLog::new("com.example.oslog", "Sync").caller_location(true);
+        let child = log.child("Network").child("Retries");
+        assert_eq!(child.subsystem, "com.example.oslog");
+        assert_eq!(child.category, "Sync.Network.Retries");
+        assert!(child.caller_location);
+
+        assert_eq!(OsLog::global().child("Network").category, "Network");
+    }
+
+    #[test]
+    fn test_disabled() {
+        let log = OsLog::disabled();
+        assert!(!log.level_is_enabled(Level::Fault));
+        log.fault("Hello");
+        assert_ne!(log.inner, OsLog::global().inner);
+    }
+
+    #[test]
+    fn test_from_swift_handle() {
+        let swift = OsLog::new("com.example.oslog", "swift");
+        let log = unsafe {
+            OsLog::from_swift_handle(swift.inner as *mut c_void, "com.example.oslog", "swift")
+        };
+        drop(swift);
+        log.default("Hello");
+        assert_eq!(log.child("Child").category, "swift.Child");
+
+        let global =
+            unsafe { OsLog::from_swift_handle(OsLog::global().inner as *mut c_void, "", "") };
+        global.default("Hello");
+    }
+
     #[test]
     fn test_subsystem_interior_null() {
         let log = OsLog::new("com.example.oslog\0test", "category");
@@ -125,6 +1037,63 @@ mod tests {
         log.with_level(Level::Debug, "Hi");
     }
 
+    #[test]
+    fn test_try_new() {
+        assert!(OsLog::try_new("com.example.oslog", "category").is_ok());
+        assert!(matches!(
+            OsLog::try_new("com.example oslog", "category"),
+            Err(Error::InvalidSubsystem(NameError::InvalidCharacter {
+                position: 11
+            }))
+        ));
+        assert!(matches!(
+            OsLog::try_new("com.example.oslog", ""),
+            Err(Error::InvalidCategory(NameError::Empty))
+        ));
+        assert_eq!(
+            OsLog::try_new("com.example.oslog", "PointsOfInterest").err(),
+            Some(Error::ReservedCategory("PointsOfInterest".into()))
+        );
+
+        let log = OsLog::points_of_interest("com.example.oslog");
+        assert_eq!(log.category, "PointsOfInterest");
+        OsLog::dynamic_tracing("com.example.oslog");
+        OsLog::dynamic_stack_tracing("com.example.oslog");
+    }
+
+    #[test]
+    fn test_eq_and_hash() {
+        use std::collections::HashSet;
+
+        let log = OsLog::new("com.example.oslog", "category");
+        assert!(
+            log == OsLog::new("com.example.oslog", "category").truncation(Truncation::Chunk, 16)
+        );
+        assert!(log != OsLog::new("com.example.oslog", "other"));
+        assert!(log != OsLog::global());
+
+        let logs: HashSet<_> = vec![
+            OsLog::new("com.example.oslog", "category"),
+            OsLog::new("com.example.oslog", "category"),
+            OsLog::global(),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(logs.len(), 2);
+    }
+
+    #[test]
+    fn test_message_types() {
+        let log = OsLog::new("com.example.oslog", "category").truncation(Truncation::Chunk, 4);
+        let count = 2;
+        log.info(cstr!("Hello"));
+        log.error(format_args!("Hello {}", count));
+        log.with_level(Level::Debug, &CString::new("Hello").unwrap());
+        log.fault(&Cow::Borrowed("Hello"));
+        log.log_fmt(Level::Info, format_args!("Hello {}", "a".repeat(1024)));
+        assert!(log.try_with_level(Level::Info, cstr!("Hello")).is_ok());
+    }
+
     #[test]
     fn test_message_interior_null() {
         let log = OsLog::new("com.example.oslog", "category");
@@ -176,4 +1145,152 @@ mod tests {
         log.error("Error");
         log.fault("Fault");
     }
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "off", not(target_vendor = "apple")),
+        ignore = "nothing is emitted"
+    )]
+    fn test_truncation() {
+        let long = "Hello, world";
+
+        let log = OsLog::new("com.example.oslog", "testing").truncation(Truncation::Ellipsis, 5);
+        log.debug(long);
+        assert!(log.try_with_level(Level::Debug, long).is_ok());
+
+        let log = OsLog::new("com.example.oslog", "testing").truncation(Truncation::Chunk, 5);
+        log.debug(long);
+        assert!(log.try_with_level(Level::Debug, long).is_ok());
+
+        let log = OsLog::new("com.example.oslog", "testing").truncation(Truncation::Error, 5);
+        log.debug(long);
+        assert_eq!(
+            log.try_with_level(Level::Debug, long),
+            Err(Error::MessageTooLong {
+                length: 12,
+                max_length: 5
+            })
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "off", not(target_vendor = "apple")),
+        ignore = "nothing is emitted"
+    )]
+    fn test_nul_bytes() {
+        let log = OsLog::new("com.example.oslog", "testing").nul_bytes(NulBytes::Strip);
+        assert!(log.try_with_level(Level::Debug, "Hi\0test").is_ok());
+
+        let log = OsLog::new("com.example.oslog", "testing").nul_bytes(NulBytes::Replace("?"));
+        assert!(log.try_with_level(Level::Debug, "Hi\0test").is_ok());
+
+        let log = OsLog::new("com.example.oslog", "testing").nul_bytes(NulBytes::Error);
+        log.debug("Hi\0test");
+        assert_eq!(
+            log.try_with_level(Level::Debug, "Hi\0test"),
+            Err(Error::InteriorNul { position: 2 })
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "off", ignore = "nothing is emitted")]
+    fn test_nul_bytes_before_truncation() {
+        let log = OsLog::new("com.example.oslog", "testing")
+            .nul_bytes(NulBytes::Replace("(null)"))
+            .truncation(Truncation::Error, 5);
+        assert_eq!(
+            log.try_with_level(Level::Debug, "Hi\0"),
+            Err(Error::MessageTooLong {
+                length: 8,
+                max_length: 5
+            })
+        );
+
+        let log = log.truncation(Truncation::Ellipsis, 5);
+        assert!(log.try_with_level(Level::Debug, "Hi\0").is_ok());
+    }
+
+    #[test]
+    fn test_expected_errors() {
+        let log = OsLog::new("com.example.oslog", "category");
+        log.expected_error("config.toml not found, using defaults");
+        log.unexpected_error("cache index out of bounds");
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = Path::new(OsStr::from_bytes(b"/tmp/\xffinvalid"));
+
+        let log = OsLog::new("com.example.oslog", "testing");
+        log.log_bytes(Level::Debug, b"\xffinvalid");
+        log.log_os_str(Level::Debug, path.as_os_str());
+        log.log_path(Level::Debug, path);
+
+        let log = OsLog::new("com.example.oslog", "testing").invalid_utf8(InvalidUtf8::Escape);
+        log.log_bytes(Level::Debug, b"\xffinvalid");
+        log.log_os_str(Level::Debug, path.as_os_str());
+        log.log_path(Level::Debug, path);
+    }
+
+    #[test]
+    fn test_fault_signal_safe() {
+        let log = OsLog::new("com.example.oslog", "testing");
+        log.fault_signal_safe(CStr::from_bytes_with_nul(b"Signal\0").unwrap());
+    }
+
+    #[test]
+    fn test_caller_location() {
+        let log = OsLog::new("com.example.oslog", "testing").caller_location(true);
+        let line = line!() + 1;
+        let message = log.locate("Hi");
+
+        assert_eq!(message, format!("Hi ({}:{})", file!(), line));
+
+        log.debug("Debug");
+        log.with_level(Level::Debug, "Debug");
+        log.fault("Fault");
+
+        let log = OsLog::new("com.example.oslog", "testing");
+        assert_eq!(log.locate("Hi"), "Hi");
+    }
+
+    #[test]
+    fn test_level_enabled() {
+        let log = OsLog::new("com.example.oslog", "testing");
+        assert_eq!(log.debug_enabled(), log.level_is_enabled(Level::Debug));
+        assert_eq!(log.info_enabled(), log.level_is_enabled(Level::Info));
+    }
+
+    #[test]
+    fn test_lazy() {
+        let log = OsLog::new("com.example.oslog", "testing");
+        let calls = std::cell::Cell::new(0);
+        let message = || {
+            calls.set(calls.get() + 1);
+            "Lazy".to_string()
+        };
+
+        log.with_level_lazy(Level::Debug, message);
+        log.debug_lazy(message);
+        log.info_lazy(message);
+        log.default_lazy(message);
+        log.error_lazy(message);
+        log.fault_lazy(message);
+
+        let enabled = [
+            Level::Debug,
+            Level::Debug,
+            Level::Info,
+            Level::Default,
+            Level::Error,
+            Level::Fault,
+        ]
+        .iter()
+        .filter(|level| log.level_is_enabled(**level))
+        .count();
+        assert_eq!(calls.get(), enabled);
+    }
 }
@@ -0,0 +1,52 @@
+use std::ffi::CStr;
+
+/// Where a message was logged from, as captured by `log_with_location!`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub module: &'static CStr,
+    pub file: &'static CStr,
+    pub line: u32,
+}
+
+/// Logs a formatted message with the module, file and line it was logged
+/// from passed as separate os_log arguments, rather than formatted in to the
+/// message.
+///
+/// ```no_run
+/// # use oslog::{log_with_location, Level, OsLog};
+/// let log = OsLog::new("com.example.test", "Parsing");
+/// log_with_location!(log, Level::Error, "Unexpected token {}", "}");
+/// ```
+#[macro_export]
+macro_rules! log_with_location {
+    ($log:expr, $level:expr, $($arg:tt)+) => {
+        $log.with_source_location(
+            $level,
+            &::std::format!($($arg)+),
+            $crate::SourceLocation {
+                module: ::std::ffi::CStr::from_bytes_with_nul(
+                    ::std::concat!(::std::module_path!(), "\0").as_bytes(),
+                )
+                .unwrap(),
+                file: ::std::ffi::CStr::from_bytes_with_nul(
+                    ::std::concat!(::std::file!(), "\0").as_bytes(),
+                )
+                .unwrap(),
+                line: ::std::line!(),
+            },
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Level, OsLog};
+
+    #[test]
+    fn test_log_with_location() {
+        let log = OsLog::new("com.example.oslog", "testing");
+        log_with_location!(log, Level::Debug, "Debug");
+        log_with_location!(log, Level::Error, "Error {}", 1);
+        log_with_location!(OsLog::global(), Level::Info, "Info");
+    }
+}
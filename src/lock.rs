@@ -0,0 +1,214 @@
+//! Locks which show waits for them in Instruments, for finding contention.
+
+use crate::signpost::mach_time;
+use crate::sys::*;
+use crate::{OsLog, SignpostId};
+use std::sync::{LockResult, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::sync::{TryLockError, TryLockResult};
+use std::time::{Duration, Instant};
+
+/// Waits shorter than this aren't shown unless the threshold is changed.
+pub const DEFAULT_THRESHOLD: Duration = Duration::from_millis(1);
+
+/// Where a lock's waits are logged, and which are long enough to be.
+struct WaitLog {
+    log: &'static OsLog,
+    name: &'static str,
+    threshold: Duration,
+}
+
+impl WaitLog {
+    /// Acquires the lock, uncontended locks without reading the clock, and
+    /// emits a "LockWait" interval if it took at least the threshold.
+    fn acquire<G>(
+        &self,
+        mode: &str,
+        try_acquire: impl FnOnce() -> TryLockResult<G>,
+        acquire: impl FnOnce() -> LockResult<G>,
+    ) -> LockResult<G> {
+        match try_acquire() {
+            Ok(guard) => return Ok(guard),
+            Err(TryLockError::Poisoned(error)) => return Err(error),
+            Err(TryLockError::WouldBlock) => {}
+        }
+
+        let start = Instant::now();
+        let result = acquire();
+        let end = Instant::now();
+
+        if end - start >= self.threshold {
+            self.emit(mode, start, end);
+        }
+
+        result
+    }
+
+    fn emit(&self, mode: &str, start: Instant, end: Instant) {
+        if !self.log.signposts_enabled() {
+            return;
+        }
+
+        let id = SignpostId::generate(self.log);
+        let wait = (end - start).as_nanos() as u64;
+        let (start, end) = (mach_time(start), mach_time(end));
+
+        self.log.emit_signpost(self.name, mode, |message| unsafe {
+            wrapped_os_signpost_lock_wait(
+                self.log.inner,
+                id.as_u64(),
+                message.as_ptr(),
+                start,
+                end,
+                wait,
+            )
+        });
+    }
+}
+
+/// A `Mutex` which emits a "LockWait" signpost interval, with the wait in
+/// nanoseconds as an integer argument, whenever locking it blocks for at
+/// least the threshold.
+///
+/// ```no_run
+/// use oslog::lock::SignpostMutex;
+/// use std::time::Duration;
+///
+/// oslog::define_log!(LOCKS, "com.example.test", "Locks");
+///
+/// let cache = SignpostMutex::new(Vec::<u8>::new(), &LOCKS, "cache")
+///     .threshold(Duration::from_micros(100));
+/// cache.lock().unwrap().push(1);
+/// ```
+pub struct SignpostMutex<T> {
+    wait_log: WaitLog,
+    inner: Mutex<T>,
+}
+
+impl<T> SignpostMutex<T> {
+    /// Waits are named `name` and logged to `log`.
+    pub fn new(value: T, log: &'static OsLog, name: &'static str) -> Self {
+        Self {
+            wait_log: WaitLog {
+                log,
+                name,
+                threshold: DEFAULT_THRESHOLD,
+            },
+            inner: Mutex::new(value),
+        }
+    }
+
+    pub fn threshold(mut self, threshold: Duration) -> Self {
+        self.wait_log.threshold = threshold;
+        self
+    }
+
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+        self.wait_log
+            .acquire("", || self.inner.try_lock(), || self.inner.lock())
+    }
+
+    pub fn try_lock(&self) -> TryLockResult<MutexGuard<'_, T>> {
+        self.inner.try_lock()
+    }
+
+    pub fn into_inner(self) -> LockResult<T> {
+        self.inner.into_inner()
+    }
+}
+
+/// An `RwLock` which emits a "LockWait" signpost interval, like
+/// `SignpostMutex`, with "read" or "write" in the message.
+pub struct SignpostRwLock<T> {
+    wait_log: WaitLog,
+    inner: RwLock<T>,
+}
+
+impl<T> SignpostRwLock<T> {
+    /// Waits are named `name` and logged to `log`.
+    pub fn new(value: T, log: &'static OsLog, name: &'static str) -> Self {
+        Self {
+            wait_log: WaitLog {
+                log,
+                name,
+                threshold: DEFAULT_THRESHOLD,
+            },
+            inner: RwLock::new(value),
+        }
+    }
+
+    pub fn threshold(mut self, threshold: Duration) -> Self {
+        self.wait_log.threshold = threshold;
+        self
+    }
+
+    pub fn read(&self) -> LockResult<RwLockReadGuard<'_, T>> {
+        self.wait_log
+            .acquire("read", || self.inner.try_read(), || self.inner.read())
+    }
+
+    pub fn write(&self) -> LockResult<RwLockWriteGuard<'_, T>> {
+        self.wait_log
+            .acquire("write", || self.inner.try_write(), || self.inner.write())
+    }
+
+    pub fn into_inner(self) -> LockResult<T> {
+        self.inner.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    crate::define_log!(LOCKS, "com.example.oslog", "lock");
+
+    #[test]
+    fn test_mutex() {
+        let mutex = Arc::new(SignpostMutex::new(0, &LOCKS, "test").threshold(Duration::ZERO));
+        let guard = mutex.lock().unwrap();
+
+        let contended = Arc::clone(&mutex);
+        let handle = thread::spawn(move || *contended.lock().unwrap() += 1);
+
+        thread::sleep(Duration::from_millis(10));
+        drop(guard);
+        handle.join().unwrap();
+
+        assert!(mutex.try_lock().is_ok());
+        assert_eq!(
+            Arc::try_unwrap(mutex).ok().unwrap().into_inner().unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_rwlock() {
+        let lock = Arc::new(SignpostRwLock::new(0, &LOCKS, "test").threshold(Duration::ZERO));
+        let guard = lock.read().unwrap();
+        assert_eq!(*lock.read().unwrap(), 0);
+
+        let contended = Arc::clone(&lock);
+        let handle = thread::spawn(move || *contended.write().unwrap() += 1);
+
+        thread::sleep(Duration::from_millis(10));
+        drop(guard);
+        handle.join().unwrap();
+
+        assert_eq!(*lock.read().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_poisoned() {
+        let mutex = Arc::new(SignpostMutex::new(0, &LOCKS, "test"));
+        let poisoner = Arc::clone(&mutex);
+        let _ = thread::spawn(move || {
+            let _guard = poisoner.lock().unwrap();
+            panic!("Expected");
+        })
+        .join();
+
+        assert!(mutex.lock().is_err());
+    }
+}
@@ -1,33 +1,98 @@
-use crate::OsLog;
+use crate::session::{self, SessionMarkers};
+use crate::stats::{self, LogStats, Stats};
+use crate::{Level, NulBytes, OsLog, Truncation};
 use dashmap::DashMap;
 use log::{LevelFilter, Log, Metadata, Record};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Records with this target are logged as faults, to the category they'd
+/// have without a target. Usually used through `fault!`.
+pub const FAULT_TARGET: &str = "oslog::fault";
+
+/// Logs a fault through the `log` crate, for conditions which should trigger
+/// the system's fault diagnostics. Useful with `OsLogger::reserve_fault`.
+///
+/// ```no_run
+/// oslog::fault!("Database corrupted: {}", "checksum mismatch");
+/// ```
+#[macro_export]
+macro_rules! fault {
+    ($($arg:tt)+) => {
+        $crate::__log::error!(target: $crate::FAULT_TARGET, $($arg)+)
+    };
+}
+
+/// A category moved out of the logger's map by `OsLogger::freeze`. Its level
+/// filter is atomic so the handle can still change it.
+struct FrozenCategory {
+    level: AtomicUsize,
+    log: OsLog,
+}
+
+impl FrozenCategory {
+    const NO_LEVEL: usize = usize::MAX;
+
+    fn new(level: Option<LevelFilter>, log: OsLog) -> Self {
+        let category = Self {
+            level: AtomicUsize::new(Self::NO_LEVEL),
+            log,
+        };
+        category.set_level(level);
+        category
+    }
+
+    fn level(&self) -> Option<LevelFilter> {
+        LevelFilter::iter().nth(self.level.load(Ordering::Relaxed))
+    }
+
+    fn set_level(&self, level: Option<LevelFilter>) {
+        let level = level.map_or(Self::NO_LEVEL, |level| level as usize);
+        self.level.store(level, Ordering::Relaxed);
+    }
+}
 
 pub struct OsLogger {
-    loggers: DashMap<String, (Option<LevelFilter>, OsLog)>,
+    // Keys are borrowed when they come from a static table.
+    loggers: DashMap<Cow<'static, str>, (Option<LevelFilter>, OsLog)>,
+    frozen: HashMap<Cow<'static, str>, FrozenCategory>,
     subsystem: String,
+    subsystem_routes: Vec<(String, String)>,
+    mirror_subsystems: Vec<String>,
+    /// Each category's logs in `mirror_subsystems`, created on first use.
+    mirrors: DashMap<Cow<'static, str>, Vec<OsLog>>,
+    default_category: Option<String>,
+    category_aliases: Vec<(String, String)>,
+    level_remaps: Vec<(String, log::Level, log::Level)>,
+    include_target: bool,
+    reserve_fault: bool,
+    truncation: Option<(Truncation, usize)>,
+    nul_bytes: NulBytes,
+    session_markers: Option<SessionMarkers>,
+    stats: Stats,
+    stats_interval: Option<Duration>,
+    stats_worker: Mutex<Option<JoinHandle<()>>>,
+    track_volume: bool,
+    /// Set by `OsLoggerHandle::shutdown`, after which nothing is logged.
+    shut_down: AtomicBool,
+    /// The level filters when the logger was installed, restored by
+    /// `OsLoggerHandle::reset`.
+    initial_levels: (LevelFilter, Vec<(Cow<'static, str>, LevelFilter)>),
 }
 
 impl Log for OsLogger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        let max_level = self
-            .loggers
-            .get(metadata.target())
-            .and_then(|pair| (*pair).0)
-            .unwrap_or_else(|| log::max_level());
-
-        metadata.level() <= max_level
+        self.enabled_for(metadata.target(), metadata.level())
     }
 
     fn log(&self, record: &Record) {
-        if self.enabled(record.metadata()) {
-            let pair = self
-                .loggers
-                .entry(record.target().into())
-                .or_insert((None, OsLog::new(&self.subsystem, record.target())));
-
-            let message = std::format!("{}", record.args());
-            (*pair).1.with_level(record.level().into(), &message);
-        }
+        let category = self.category(record);
+        let level = self.remap(category, record.level());
+        self.with_enabled_log(category, level, |log| self.log_to(log, record, category));
     }
 
     fn flush(&self) {}
@@ -39,10 +104,294 @@ impl OsLogger {
     pub fn new(subsystem: &str) -> Self {
         Self {
             loggers: DashMap::new(),
+            frozen: HashMap::new(),
             subsystem: subsystem.to_string(),
+            subsystem_routes: Vec::new(),
+            mirror_subsystems: Vec::new(),
+            mirrors: DashMap::new(),
+            default_category: None,
+            category_aliases: Vec::new(),
+            level_remaps: Vec::new(),
+            include_target: false,
+            reserve_fault: false,
+            truncation: None,
+            nul_bytes: NulBytes::default(),
+            session_markers: None,
+            stats: Stats::default(),
+            stats_interval: None,
+            stats_worker: Mutex::new(None),
+            track_volume: false,
+            shut_down: AtomicBool::new(false),
+            initial_levels: (LevelFilter::Trace, Vec::new()),
         }
     }
 
+    fn new_log(&self, category: &str) -> OsLog {
+        self.new_log_in(self.subsystem_for(category), category)
+    }
+
+    fn new_log_in(&self, subsystem: &str, category: &str) -> OsLog {
+        let mut log = OsLog::new(subsystem, category);
+        log.truncation = self.truncation;
+        log.nul_bytes = self.nul_bytes;
+        log
+    }
+
+    fn log_to(&self, log: &OsLog, record: &Record, category: &str) {
+        // Skip formatting messages the system would discard, and only format
+        // them once for the mirrors.
+        let level = self.level(record, category);
+        let mut message = None;
+        // Mirrors get the same messages, so only the primary log is counted.
+        let mut emit = |log: &OsLog, primary: bool| {
+            if log.level_is_enabled(level) {
+                let message = message.get_or_insert_with(|| self.message(record, category));
+                let logged = log.try_with_level(level, &*message).is_ok();
+                if primary {
+                    self.count(category, message, logged);
+                }
+            }
+        };
+
+        emit(log, true);
+
+        if !self.mirror_subsystems.is_empty() {
+            if let Some(mirrors) = self.mirrors.get(category) {
+                mirrors.iter().for_each(|log| emit(log, false));
+            } else {
+                let mirrors = self
+                    .mirrors
+                    .entry(Cow::Owned(category.into()))
+                    .or_insert_with(|| {
+                        self.mirror_subsystems
+                            .iter()
+                            .map(|subsystem| self.new_log_in(subsystem, category))
+                            .collect()
+                    });
+                // See `with_enabled_log`.
+                if self.is_shut_down() {
+                    drop(mirrors);
+                    self.mirrors.remove(category);
+                    return;
+                }
+                mirrors.iter().for_each(|log| emit(log, false));
+            }
+        }
+    }
+
+    fn count(&self, category: &str, message: &str, logged: bool) {
+        if !logged {
+            self.stats.dropped(category);
+            return;
+        }
+
+        if self.track_volume {
+            self.stats.logged(category, message.len());
+        }
+
+        if let Some((Truncation::Ellipsis, max_length)) = self.truncation {
+            if message.len() > max_length {
+                self.stats.truncated(category);
+            }
+        }
+    }
+
+    /// Calls `f` with the category's log if `level` passes its level filter,
+    /// with a single lookup when the category exists. Otherwise the log is
+    /// only created if `level` passes the global filter.
+    fn with_enabled_log<T>(
+        &self,
+        category: &str,
+        level: log::Level,
+        f: impl FnOnce(&OsLog) -> T,
+    ) -> Option<T> {
+        if cfg!(feature = "off") || self.is_shut_down() {
+            return None;
+        }
+
+        let enabled = |filter: Option<LevelFilter>| level <= filter.unwrap_or_else(log::max_level);
+
+        if let Some(frozen) = self.frozen.get(category) {
+            return enabled(frozen.level()).then(|| f(&frozen.log));
+        }
+
+        if let Some(pair) = self.loggers.get(category) {
+            return enabled(pair.0).then(|| f(&pair.1));
+        }
+
+        if !enabled(None) {
+            return None;
+        }
+
+        // Another thread may have added the category, with a filter, since.
+        let pair = self
+            .loggers
+            .entry(Cow::Owned(category.into()))
+            .or_insert_with(|| (None, self.new_log(category)));
+        // Shutting down sets the flag before clearing the map, so if this
+        // entry was inserted after the clear, taking its shard's lock has
+        // made the flag visible here.
+        if self.is_shut_down() {
+            drop(pair);
+            self.loggers.remove(category);
+            return None;
+        }
+        enabled(pair.0).then(|| f(&pair.1))
+    }
+
+    /// Returns true if a record with `target` and `level` would pass the
+    /// logger's filters and is enabled by the system's configuration for its
+    /// category, e.g. set with `sudo log config`. The category's log is
+    /// created if it doesn't exist yet.
+    pub fn enabled_for(&self, target: &str, level: log::Level) -> bool {
+        let record = Record::builder().target(target).level(level).build();
+        let category = self.category(&record);
+        let level = self.remap(category, level);
+
+        self.with_enabled_log(category, level, |log| {
+            log.level_is_enabled(self.level(&record, category))
+        })
+        .unwrap_or(false)
+    }
+
+    /// Records without an explicit target have their module path as the
+    /// target. That's decided from the target alone, since `Log::enabled`
+    /// isn't given the module path, so it agrees with `log`.
+    fn category<'a>(&'a self, record: &Record<'a>) -> &'a str {
+        let (target, untargeted) = match record.target() {
+            FAULT_TARGET => (record.module_path().unwrap_or(FAULT_TARGET), true),
+            target => (target, is_module_path(target)),
+        };
+
+        if let Some((_, alias)) = self
+            .category_aliases
+            .iter()
+            .find(|(module, _)| in_module(module, target))
+        {
+            return alias;
+        }
+
+        match &self.default_category {
+            Some(category) if untargeted && !self.is_routed(target) => category,
+            _ => target,
+        }
+    }
+
+    fn remap(&self, category: &str, level: log::Level) -> log::Level {
+        self.level_remaps
+            .iter()
+            .find(|(remapped, from, _)| remapped == category && *from == level)
+            .map_or(level, |(_, _, to)| *to)
+    }
+
+    fn level(&self, record: &Record, category: &str) -> Level {
+        match self.remap(category, record.level()) {
+            _ if record.target() == FAULT_TARGET => Level::Fault,
+            log::Level::Error if self.reserve_fault => Level::Error,
+            level => level.into(),
+        }
+    }
+
+    fn message(&self, record: &Record, category: &str) -> String {
+        #[allow(unused_mut)]
+        let mut message = if self.include_target && record.target() != category {
+            std::format!("[{}] {}", record.target(), record.args())
+        } else {
+            std::format!("{}", record.args())
+        };
+
+        #[cfg(feature = "kv")]
+        message.push_str(&crate::kv::render(record));
+
+        message
+    }
+
+    fn is_routed(&self, target: &str) -> bool {
+        self.subsystem_routes
+            .iter()
+            .any(|(pattern, _)| target_matches(pattern, target))
+    }
+
+    fn subsystem_for(&self, target: &str) -> &str {
+        self.subsystem_routes
+            .iter()
+            .find(|(pattern, _)| target_matches(pattern, target))
+            .map_or(&self.subsystem, |(_, subsystem)| subsystem)
+    }
+
+    /// Logs records whose target matches `pattern` to `subsystem` instead of
+    /// the logger's. A pattern ending in `::*` matches the module and all of
+    /// its submodules, e.g. "vendor_sdk::*" matches "vendor_sdk" and
+    /// "vendor_sdk::net", otherwise the target must match exactly. When
+    /// several patterns match, the first added wins.
+    pub fn with_subsystem_for_target(mut self, pattern: &str, subsystem: &str) -> Self {
+        self.subsystem_routes
+            .push((pattern.to_string(), subsystem.to_string()));
+
+        for mut pair in self.loggers.iter_mut() {
+            if target_matches(pattern, pair.key()) {
+                let level = pair.0;
+                *pair = (level, self.new_log(pair.key()));
+            }
+        }
+
+        self
+    }
+
+    /// Also logs every record to `subsystem`, in the same category and at the
+    /// same level, e.g. to feed an organization-wide diagnostics subsystem
+    /// alongside the app's own.
+    pub fn mirror_to_subsystem(mut self, subsystem: &str) -> Self {
+        self.mirror_subsystems.push(subsystem.to_string());
+        self.mirrors.clear();
+        self
+    }
+
+    /// Logs records without an explicit target to `category`, rather than a
+    /// category named after the module they were logged from. Records routed
+    /// to another subsystem keep their module's category.
+    ///
+    /// Any target which looks like a module path, i.e. lowercase identifiers
+    /// separated by `::`, counts as having no explicit target, so give
+    /// explicit targets a capital letter, e.g. "Settings".
+    pub fn with_default_category(mut self, category: &str) -> Self {
+        self.default_category = Some(category.to_string());
+        self
+    }
+
+    /// Logs records from `module` and its submodules to `category`, e.g.
+    /// "hyper::proto" to "Networking", to fold noisy dependencies into fewer
+    /// categories. Aliases take priority over the default category, and when
+    /// several match, the first added wins. Subsystem routes are matched
+    /// against `category`.
+    pub fn with_category_alias(mut self, module: &str, category: &str) -> Self {
+        self.category_aliases
+            .push((module.to_string(), category.to_string()));
+        self
+    }
+
+    /// Prefixes messages with "[target] " when they're logged to a category
+    /// other than their target, so the target isn't lost.
+    pub fn include_target(mut self, enabled: bool) -> Self {
+        self.include_target = enabled;
+        self
+    }
+
+    /// Treats records in `category` at level `from` as if they were logged at
+    /// `to`, e.g. to demote a dependency's warnings to info, before filtering
+    /// and mapping them to os_log levels.
+    pub fn with_level_remap(mut self, category: &str, from: log::Level, to: log::Level) -> Self {
+        self.level_remaps.push((category.to_string(), from, to));
+        self
+    }
+
+    /// Maps `log::Level::Error` to the error level rather than fault, leaving
+    /// faults to records logged with `fault!`.
+    pub fn reserve_fault(mut self, enabled: bool) -> Self {
+        self.reserve_fault = enabled;
+        self
+    }
+
     /// Only levels at or above `level` will be logged.
     pub fn level_filter(self, level: LevelFilter) -> Self {
         log::set_max_level(level);
@@ -52,18 +401,373 @@ impl OsLogger {
     /// Sets or updates the category's level filter.
     pub fn category_level_filter(self, category: &str, level: LevelFilter) -> Self {
         self.loggers
-            .entry(category.into())
+            .entry(Cow::Owned(category.into()))
             .and_modify(|(existing_level, _)| *existing_level = Some(level))
-            .or_insert((Some(level), OsLog::new(&self.subsystem, category)));
+            .or_insert_with(|| (Some(level), self.new_log(category)));
+
+        self
+    }
+
+    /// Sets or updates the level filters of each category in `levels`. The
+    /// category names are borrowed from the table rather than copied.
+    ///
+    /// ```no_run
+    /// use log::LevelFilter;
+    /// use oslog::OsLogger;
+    ///
+    /// static LEVELS: &[(&str, LevelFilter)] = &[
+    ///     ("Settings", LevelFilter::Warn),
+    ///     ("Database", LevelFilter::Error),
+    /// ];
+    ///
+    /// OsLogger::new("com.example.test")
+    ///     .category_level_filters(LEVELS)
+    ///     .init()
+    ///     .unwrap();
+    /// ```
+    pub fn category_level_filters(self, levels: &'static [(&'static str, LevelFilter)]) -> Self {
+        for &(category, level) in levels {
+            self.loggers
+                .entry(Cow::Borrowed(category))
+                .and_modify(|(existing_level, _)| *existing_level = Some(level))
+                .or_insert_with(|| (Some(level), self.new_log(category)));
+        }
+
+        self
+    }
+
+    /// Messages longer than `max_length` bytes will be handled according to
+    /// `policy`. Messages rejected by `Truncation::Error` are dropped.
+    pub fn truncation(mut self, policy: Truncation, max_length: usize) -> Self {
+        self.truncation = Some((policy, max_length));
+
+        for mut pair in self.loggers.iter_mut() {
+            pair.1.truncation = self.truncation;
+        }
+
+        self
+    }
+
+    /// NUL bytes in messages will be handled according to `policy`. Messages
+    /// rejected by `NulBytes::Error` are dropped.
+    pub fn nul_bytes(mut self, policy: NulBytes) -> Self {
+        self.nul_bytes = policy;
+
+        for mut pair in self.loggers.iter_mut() {
+            pair.1.nul_bytes = policy;
+        }
 
         self
     }
 
+    /// Moves the categories created so far, e.g. by `category_level_filter`,
+    /// into a map which is read without locking. Call it after the rest of
+    /// the configuration, since later builder calls don't apply to frozen
+    /// categories. Categories first logged to afterwards are still created
+    /// on demand.
+    pub fn freeze(mut self) -> Self {
+        for (category, (level, log)) in std::mem::take(&mut self.loggers) {
+            self.frozen
+                .insert(category, FrozenCategory::new(level, log));
+        }
+
+        self
+    }
+
+    /// Logs "process started" with the version, build and a hash of the
+    /// arguments to the `Session` category when the logger is installed,
+    /// and "process exiting" with the uptime when the process exits
+    /// normally.
+    ///
+    /// ```no_run
+    /// # use oslog::OsLogger;
+    /// OsLogger::new("com.example.app")
+    ///     .session_markers(env!("CARGO_PKG_VERSION"), "42")
+    ///     .init()
+    ///     .unwrap();
+    /// ```
+    pub fn session_markers(mut self, version: &str, build: &str) -> Self {
+        self.session_markers = Some(SessionMarkers {
+            version: version.to_string(),
+            build: build.to_string(),
+        });
+        self
+    }
+
+    /// Counts the messages and bytes each category logs, for finding which
+    /// ones dominate the log volume. Each message then costs a lookup in the
+    /// statistics map.
+    pub fn track_volume(mut self, enabled: bool) -> Self {
+        self.track_volume = enabled;
+        self
+    }
+
+    /// Logs the statistics of each category whose counts changed, to the
+    /// `Stats` category, every `interval` once the logger is installed.
+    pub fn log_stats_every(mut self, interval: Duration) -> Self {
+        self.stats_interval = Some(interval);
+        self
+    }
+
+    /// The session's log and markers, which are logged once the logger has
+    /// been installed.
+    fn take_session(&mut self) -> Option<(OsLog, SessionMarkers)> {
+        let markers = self.session_markers.take()?;
+        let log = self.new_log_in(&self.subsystem, session::SESSION_CATEGORY);
+        Some((log, markers))
+    }
+
     pub fn init(self) -> Result<(), log::SetLoggerError> {
-        log::set_boxed_logger(Box::new(self))
+        self.init_with_handle().map(|_| ())
+    }
+
+    /// Like `init`, but returns a handle for changing level filters while
+    /// the program is running.
+    pub fn init_with_handle(self) -> Result<OsLoggerHandle, log::SetLoggerError> {
+        let logger = self.install()?;
+        let _ = INSTALLED.set(Some(logger));
+        Ok(OsLoggerHandle { logger })
+    }
+
+    /// Installs the logger `configure` returns unless one has already been
+    /// installed, so libraries and tests can initialize logging without
+    /// coordinating. The first configuration wins, and every call returns a
+    /// handle to it, or `None` if a logger from another crate was installed.
+    ///
+    /// ```
+    /// use log::LevelFilter;
+    /// use oslog::OsLogger;
+    ///
+    /// let first = OsLogger::init_once(|| OsLogger::new("com.example.test"));
+    /// let second = OsLogger::init_once(|| {
+    ///     OsLogger::new("com.example.other").level_filter(LevelFilter::Trace)
+    /// });
+    /// assert!(first.is_some());
+    /// assert!(second.is_some());
+    /// ```
+    pub fn init_once(configure: impl FnOnce() -> Self) -> Option<OsLoggerHandle> {
+        INSTALLED
+            .get_or_init(|| configure().install().ok())
+            .map(|logger| OsLoggerHandle { logger })
+    }
+
+    fn install(mut self) -> Result<&'static OsLogger, log::SetLoggerError> {
+        let session = self.take_session();
+
+        // Only leaked once `log` has kept it, so a failed install frees it.
+        let logger = Box::into_raw(self.boxed());
+        let logger: &'static OsLogger = match log::set_logger(unsafe { &*logger }) {
+            Ok(()) => unsafe { &*logger },
+            Err(error) => {
+                drop(unsafe { Box::from_raw(logger) });
+                return Err(error);
+            }
+        };
+
+        if let Some((log, markers)) = session {
+            session::start(log, &markers);
+        }
+
+        logger.start_stats_worker();
+        Ok(logger)
+    }
+
+    /// Records the level filters to reset to, and boxes the logger for `log`
+    /// to keep for the rest of the process.
+    fn boxed(mut self) -> Box<OsLogger> {
+        self.initial_levels = (log::max_level(), self.category_levels());
+        Box::new(self)
+    }
+
+    /// Leaks the logger without installing it, for `test_util::swap`.
+    #[cfg(feature = "test-util")]
+    pub(crate) fn into_handle(self) -> OsLoggerHandle {
+        OsLoggerHandle {
+            logger: Box::leak(self.boxed()),
+        }
+    }
+
+    fn start_stats_worker(&'static self) {
+        if let Some(interval) = self.stats_interval {
+            let log = self.new_log_in(&self.subsystem, stats::STATS_CATEGORY);
+            let worker = thread::Builder::new()
+                .name("oslog-stats".into())
+                .spawn(move || self.report_stats(&log, interval));
+            *self.stats_worker.lock().unwrap_or_else(|e| e.into_inner()) = worker.ok();
+        }
+    }
+
+    /// Discards everything logged from now on, stops the statistics thread
+    /// and releases the cached logs. Frozen categories are read without
+    /// locking, so their logs are kept.
+    fn shut_down(&self) {
+        self.shut_down.store(true, Ordering::Release);
+        self.flush();
+
+        let worker = self
+            .stats_worker
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        if let Some(worker) = worker {
+            worker.thread().unpark();
+            let _ = worker.join();
+        }
+
+        self.loggers.clear();
+        self.mirrors.clear();
+    }
+
+    fn is_shut_down(&self) -> bool {
+        self.shut_down.load(Ordering::Acquire)
+    }
+
+    fn report_stats(&self, log: &OsLog, interval: Duration) {
+        let mut previous = HashMap::new();
+
+        loop {
+            // Parked rather than asleep so shutting down needn't wait out the
+            // interval.
+            let deadline = Instant::now() + interval;
+            while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+                if self.is_shut_down() {
+                    return;
+                }
+                thread::park_timeout(remaining);
+            }
+
+            let current = self.stats.snapshot();
+            for line in stats::summary(&current, &previous) {
+                log.with_level(Level::Default, &line);
+            }
+            previous = current;
+        }
+    }
+
+    fn category_levels(&self) -> Vec<(Cow<'static, str>, LevelFilter)> {
+        let frozen = self
+            .frozen
+            .iter()
+            .filter_map(|(category, frozen)| frozen.level().map(|level| (category.clone(), level)));
+
+        self.loggers
+            .iter()
+            .filter_map(|pair| pair.0.map(|level| (pair.key().clone(), level)))
+            .chain(frozen)
+            .collect()
     }
 }
 
+/// The logger installed by this crate, or `None` if `init_once` found
+/// another crate's.
+static INSTALLED: OnceLock<Option<&'static OsLogger>> = OnceLock::new();
+
+/// Changes an installed `OsLogger`'s level filters, e.g. to turn up a
+/// category while debugging, and reads its statistics.
+#[derive(Clone, Copy)]
+pub struct OsLoggerHandle {
+    logger: &'static OsLogger,
+}
+
+impl OsLoggerHandle {
+    /// Only levels at or above `level` will be logged.
+    pub fn set_level_filter(&self, level: LevelFilter) {
+        log::set_max_level(level);
+    }
+
+    /// Sets or updates the category's level filter.
+    pub fn set_category_level_filter(&self, category: &str, level: LevelFilter) {
+        if let Some(frozen) = self.logger.frozen.get(category) {
+            frozen.set_level(Some(level));
+            return;
+        }
+
+        self.logger
+            .loggers
+            .entry(Cow::Owned(category.into()))
+            .and_modify(|(existing_level, _)| *existing_level = Some(level))
+            .or_insert_with(|| (Some(level), self.logger.new_log(category)));
+    }
+
+    /// Removes the category's level filter, so it follows the global one.
+    pub fn remove_category_level_filter(&self, category: &str) {
+        if let Some(frozen) = self.logger.frozen.get(category) {
+            frozen.set_level(None);
+            return;
+        }
+
+        if let Some(mut pair) = self.logger.loggers.get_mut(category) {
+            pair.0 = None;
+        }
+    }
+
+    /// The dropped and truncated message counts of each category which has
+    /// lost messages, or of every category with `OsLogger::track_volume`.
+    pub fn stats(&self) -> HashMap<String, LogStats> {
+        self.logger.stats.snapshot()
+    }
+
+    #[cfg(feature = "test-util")]
+    pub(crate) fn logger(&self) -> &'static OsLogger {
+        self.logger
+    }
+
+    /// Stops the logger for good, for libraries which are about to be
+    /// unloaded: the statistics thread is joined, the cached logs released,
+    /// and everything logged afterwards is discarded. Categories from a
+    /// static table or `freeze` keep their logs, as do session markers,
+    /// whose exit handler can't be unregistered, so don't use them in
+    /// unloadable libraries.
+    pub fn shutdown(&self) {
+        log::set_max_level(LevelFilter::Off);
+        self.logger.shut_down();
+    }
+
+    /// Restores the level filters the logger was installed with.
+    pub fn reset(&self) {
+        let (level, category_levels) = &self.logger.initial_levels;
+
+        for mut pair in self.logger.loggers.iter_mut() {
+            pair.0 = None;
+        }
+        for frozen in self.logger.frozen.values() {
+            frozen.set_level(None);
+        }
+        for (category, level) in category_levels {
+            self.set_category_level_filter(category, *level);
+        }
+
+        log::set_max_level(*level);
+    }
+}
+
+fn target_matches(pattern: &str, target: &str) -> bool {
+    match pattern.strip_suffix("::*") {
+        Some(module) => in_module(module, target),
+        None => target == pattern,
+    }
+}
+
+/// Returns true if `target` is `module` or one of its submodules.
+/// Whether `target` could be the module path the log crate uses as the
+/// target of records without one.
+fn is_module_path(target: &str) -> bool {
+    target.split("::").all(|segment| {
+        let mut chars = segment.chars();
+        chars
+            .next()
+            .is_some_and(|first| first == '_' || first.is_ascii_lowercase())
+            && chars.all(|c| c == '_' || c.is_ascii_lowercase() || c.is_ascii_digit())
+    })
+}
+
+fn in_module(module: &str, target: &str) -> bool {
+    target == module
+        || target
+            .strip_prefix(module)
+            .is_some_and(|rest| rest.starts_with("::"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -91,4 +795,405 @@ mod tests {
         warn!(target: "Database", "Warn");
         error!("Error");
     }
+
+    #[test]
+    fn test_truncation() {
+        let logger = OsLogger::new("com.example.oslog")
+            .category_level_filter("Settings", LevelFilter::Warn)
+            .truncation(Truncation::Chunk, 1024)
+            .category_level_filter("Database", LevelFilter::Error);
+
+        for category in ["Settings", "Database"].iter() {
+            assert_eq!(
+                logger.loggers.get(*category).unwrap().1.truncation,
+                Some((Truncation::Chunk, 1024))
+            );
+        }
+    }
+
+    #[test]
+    fn test_default_category() {
+        let logger = OsLogger::new("com.example.app");
+        let record = Record::builder()
+            .target("app::net")
+            .module_path(Some("app::net"))
+            .build();
+        assert_eq!(logger.category(&record), "app::net");
+
+        let logger = logger.with_default_category("App");
+        assert_eq!(logger.category(&record), "App");
+
+        let record = Record::builder()
+            .target("Settings")
+            .module_path(Some("app::net"))
+            .build();
+        assert_eq!(logger.category(&record), "Settings");
+
+        let logger = logger.with_subsystem_for_target("vendor_sdk::*", "com.example.vendor");
+        let record = Record::builder()
+            .target("vendor_sdk::net")
+            .module_path(Some("vendor_sdk::net"))
+            .build();
+        assert_eq!(logger.category(&record), "vendor_sdk::net");
+    }
+
+    #[test]
+    fn test_is_module_path() {
+        assert!(is_module_path("app"));
+        assert!(is_module_path("app::net_2::_private"));
+        assert!(!is_module_path("Settings"));
+        assert!(!is_module_path("app::"));
+        assert!(!is_module_path("app net"));
+    }
+
+    #[test]
+    fn test_enabled_agrees_with_log() {
+        let logger = OsLogger::new("com.example.app")
+            .with_default_category("App")
+            .category_level_filter("App", LevelFilter::Error);
+        let metadata = |level| {
+            log::Metadata::builder()
+                .target(module_path!())
+                .level(level)
+                .build()
+        };
+        let record = |level| {
+            Record::builder()
+                .target(module_path!())
+                .module_path(Some(module_path!()))
+                .level(level)
+                .args(format_args!("Hello"))
+                .build()
+        };
+
+        // As log_enabled! and log! would check them.
+        let target_only = Record::builder().target(module_path!()).build();
+        assert_eq!(logger.category(&target_only), "App");
+        assert_eq!(logger.category(&record(log::Level::Info)), "App");
+        assert!(!logger.enabled(&metadata(log::Level::Info)));
+        logger.log(&record(log::Level::Info));
+
+        if cfg!(all(target_vendor = "apple", not(feature = "off"))) {
+            assert!(logger.enabled(&metadata(log::Level::Error)));
+        }
+        logger.log(&record(log::Level::Error));
+
+        // Nothing was created for the module.
+        assert!(!logger.loggers.contains_key(module_path!()));
+    }
+
+    #[test]
+    fn test_include_target() {
+        let logger = OsLogger::new("com.example.app").with_default_category("App");
+        let args = format_args!("Connected");
+        let record = Record::builder()
+            .args(args)
+            .target("app::net")
+            .module_path(Some("app::net"))
+            .build();
+        assert_eq!(logger.message(&record, "App"), "Connected");
+
+        let logger = logger.include_target(true);
+        assert_eq!(logger.message(&record, "App"), "[app::net] Connected");
+        assert_eq!(logger.message(&record, "app::net"), "Connected");
+    }
+
+    #[test]
+    fn test_reserve_fault() {
+        let logger = OsLogger::new("com.example.app");
+        let error = Record::builder()
+            .level(log::Level::Error)
+            .target("app")
+            .build();
+        let fault = Record::builder()
+            .level(log::Level::Error)
+            .target(FAULT_TARGET)
+            .module_path(Some("app::db"))
+            .build();
+
+        assert_eq!(logger.level(&error, "app"), Level::Fault);
+        assert_eq!(logger.level(&fault, "app::db"), Level::Fault);
+        assert_eq!(logger.category(&fault), "app::db");
+
+        let logger = logger.reserve_fault(true).with_default_category("App");
+        assert_eq!(logger.level(&error, "app"), Level::Error);
+        assert_eq!(logger.level(&fault, "App"), Level::Fault);
+        assert_eq!(logger.category(&fault), "App");
+    }
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "off", not(target_vendor = "apple")),
+        ignore = "nothing is emitted"
+    )]
+    fn test_enabled_for() {
+        let logger =
+            OsLogger::new("com.example.app").category_level_filter("Settings", LevelFilter::Warn);
+
+        assert!(logger.enabled_for("Settings", log::Level::Error));
+        assert!(!logger.enabled_for("Settings", log::Level::Info));
+        assert!(logger.loggers.get("Settings").is_some());
+    }
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "off", not(target_vendor = "apple")),
+        ignore = "nothing is emitted"
+    )]
+    fn test_with_enabled_log() {
+        let logger =
+            OsLogger::new("com.example.app").category_level_filter("Settings", LevelFilter::Warn);
+
+        assert_eq!(
+            logger.with_enabled_log("Settings", log::Level::Error, |log| log.category.clone()),
+            Some("Settings".to_string())
+        );
+        assert_eq!(
+            logger.with_enabled_log("Settings", log::Level::Info, |_| ()),
+            None
+        );
+        assert!(logger
+            .with_enabled_log("Database", log::Level::Error, |_| ())
+            .is_some());
+        assert!(logger.loggers.get("Database").is_some());
+    }
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "off", not(target_vendor = "apple")),
+        ignore = "nothing is emitted"
+    )]
+    fn test_level_remap() {
+        let logger = OsLogger::new("com.example.app")
+            .category_level_filter("hyper", LevelFilter::Warn)
+            .with_level_remap("hyper", log::Level::Warn, log::Level::Info)
+            .with_level_remap("hyper", log::Level::Debug, log::Level::Error);
+        let record = |level| Record::builder().level(level).target("hyper").build();
+
+        assert_eq!(
+            logger.level(&record(log::Level::Warn), "hyper"),
+            Level::Default
+        );
+        assert_eq!(
+            logger.level(&record(log::Level::Debug), "hyper"),
+            Level::Fault
+        );
+        assert_eq!(logger.level(&record(log::Level::Warn), "app"), Level::Error);
+
+        assert!(!logger.enabled_for("hyper", log::Level::Warn));
+        assert!(logger.enabled_for("hyper", log::Level::Debug));
+    }
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "off", not(target_vendor = "apple")),
+        ignore = "nothing is emitted"
+    )]
+    fn test_stats() {
+        let logger = OsLogger::new("com.example.app")
+            .category_level_filter("Stats", LevelFilter::Trace)
+            .truncation(Truncation::Ellipsis, 8)
+            .nul_bytes(NulBytes::Error)
+            .track_volume(true);
+        let log = |message: &str| {
+            logger.log(
+                &Record::builder()
+                    .level(log::Level::Info)
+                    .target("Stats")
+                    .args(format_args!("{}", message))
+                    .build(),
+            )
+        };
+
+        log("Hello");
+        assert_eq!(logger.stats.snapshot()["Stats"].bytes, 5);
+
+        log("Hello, world");
+        log("Nul\0byte");
+
+        assert_eq!(
+            logger.stats.snapshot()["Stats"],
+            LogStats {
+                dropped: 1,
+                truncated: 1,
+                messages: 2,
+                bytes: 17
+            }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "off", not(target_vendor = "apple")),
+        ignore = "nothing is emitted"
+    )]
+    fn test_shut_down() {
+        let logger: &'static OsLogger = Box::leak(Box::new(
+            OsLogger::new("com.example.app")
+                .mirror_to_subsystem("com.example.diagnostics")
+                .category_level_filter("Networking", LevelFilter::Trace)
+                .log_stats_every(Duration::from_secs(3600)),
+        ));
+        let record = Record::builder()
+            .args(format_args!("Connected"))
+            .level(log::Level::Info)
+            .target("Networking")
+            .build();
+
+        logger.start_stats_worker();
+        logger.log(&record);
+        assert!(logger.mirrors.contains_key("Networking"));
+
+        // Returns without waiting out the statistics interval.
+        logger.shut_down();
+        assert!(logger.stats_worker.lock().unwrap().is_none());
+        assert!(logger.loggers.is_empty());
+        assert!(logger.mirrors.is_empty());
+
+        logger.log(&record);
+        assert!(!logger.enabled_for("Networking", log::Level::Error));
+        assert!(logger.loggers.is_empty());
+    }
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "off", not(target_vendor = "apple")),
+        ignore = "nothing is emitted"
+    )]
+    fn test_mirror_to_subsystem() {
+        let logger = OsLogger::new("com.example.app")
+            .mirror_to_subsystem("com.example.diagnostics")
+            .mirror_to_subsystem("com.example.audit")
+            .category_level_filter("Networking", LevelFilter::Trace);
+        let args = format_args!("Connected");
+        let record = Record::builder()
+            .args(args)
+            .level(log::Level::Info)
+            .target("Networking")
+            .build();
+
+        logger.log(&record);
+        logger.log(&record);
+
+        let mirrors = logger.mirrors.get("Networking").unwrap();
+        let subsystems: Vec<_> = mirrors.iter().map(|log| log.subsystem.as_str()).collect();
+        assert_eq!(subsystems, ["com.example.diagnostics", "com.example.audit"]);
+        assert!(mirrors.iter().all(|log| log.category == "Networking"));
+    }
+
+    #[test]
+    fn test_category_alias() {
+        let logger = OsLogger::new("com.example.app")
+            .with_default_category("App")
+            .with_category_alias("hyper::proto", "Networking");
+
+        let record = |target| {
+            Record::builder()
+                .target(target)
+                .module_path(Some(target))
+                .build()
+        };
+        assert_eq!(logger.category(&record("hyper::proto")), "Networking");
+        assert_eq!(logger.category(&record("hyper::proto::h1")), "Networking");
+        assert_eq!(logger.category(&record("hyper::client")), "App");
+        assert_eq!(logger.category(&record("hyper::protocol")), "App");
+    }
+
+    #[test]
+    fn test_handle() {
+        let mut logger =
+            OsLogger::new("com.example.app").category_level_filter("Settings", LevelFilter::Warn);
+        logger.initial_levels = (LevelFilter::Trace, logger.category_levels());
+        let handle = OsLoggerHandle {
+            logger: Box::leak(Box::new(logger)),
+        };
+        let level = |category| handle.logger.loggers.get(category).and_then(|pair| pair.0);
+
+        handle.set_category_level_filter("Settings", LevelFilter::Trace);
+        handle.set_category_level_filter("Database", LevelFilter::Debug);
+        assert_eq!(level("Settings"), Some(LevelFilter::Trace));
+        assert_eq!(level("Database"), Some(LevelFilter::Debug));
+
+        handle.remove_category_level_filter("Database");
+        assert_eq!(level("Database"), None);
+
+        handle.set_category_level_filter("Database", LevelFilter::Debug);
+        handle.reset();
+        assert_eq!(level("Settings"), Some(LevelFilter::Warn));
+        assert_eq!(level("Database"), None);
+    }
+
+    #[test]
+    fn test_category_level_filters() {
+        static LEVELS: &[(&str, LevelFilter)] = &[
+            ("Settings", LevelFilter::Warn),
+            ("Database", LevelFilter::Error),
+        ];
+
+        let logger = OsLogger::new("com.example.app")
+            .category_level_filter("Settings", LevelFilter::Trace)
+            .category_level_filters(LEVELS);
+
+        assert!(!logger.enabled_for("Settings", log::Level::Info));
+        assert!(!logger.enabled_for("Database", log::Level::Warn));
+        assert!(matches!(
+            logger.loggers.get("Database").unwrap().key(),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "off", not(target_vendor = "apple")),
+        ignore = "nothing is emitted"
+    )]
+    fn test_freeze() {
+        let logger = OsLogger::new("com.example.app")
+            .category_level_filter("Settings", LevelFilter::Warn)
+            .category_level_filter("Database", LevelFilter::Off)
+            .freeze();
+
+        assert!(logger.loggers.is_empty());
+        assert_eq!(logger.frozen["Settings"].level(), Some(LevelFilter::Warn));
+        assert_eq!(logger.frozen["Database"].level(), Some(LevelFilter::Off));
+        assert!(logger.enabled_for("Settings", log::Level::Error));
+        assert!(!logger.enabled_for("Settings", log::Level::Info));
+        assert!(!logger.enabled_for("Database", log::Level::Error));
+
+        logger.frozen["Settings"].set_level(None);
+        assert_eq!(logger.frozen["Settings"].level(), None);
+
+        assert!(logger.enabled_for("Network", log::Level::Error));
+        assert!(logger.loggers.get("Network").is_some());
+    }
+
+    #[test]
+    fn test_target_matches() {
+        assert!(target_matches("vendor_sdk::*", "vendor_sdk"));
+        assert!(target_matches("vendor_sdk::*", "vendor_sdk::net"));
+        assert!(target_matches("vendor_sdk::*", "vendor_sdk::net::tls"));
+        assert!(!target_matches("vendor_sdk::*", "vendor_sdk_extra"));
+        assert!(!target_matches("vendor_sdk::*", "app::vendor_sdk"));
+        assert!(target_matches("vendor_sdk", "vendor_sdk"));
+        assert!(!target_matches("vendor_sdk", "vendor_sdk::net"));
+    }
+
+    #[test]
+    fn test_subsystem_for_target() {
+        let logger = OsLogger::new("com.example.app")
+            .category_level_filter("vendor_sdk::net", LevelFilter::Warn)
+            .with_subsystem_for_target("vendor_sdk::*", "com.example.vendor")
+            .with_subsystem_for_target("vendor_sdk::net", "com.example.ignored");
+
+        assert_eq!(logger.subsystem_for("app"), "com.example.app");
+        assert_eq!(logger.subsystem_for("vendor_sdk"), "com.example.vendor");
+        assert_eq!(
+            logger.subsystem_for("vendor_sdk::net"),
+            "com.example.vendor"
+        );
+
+        let pair = logger.loggers.get("vendor_sdk::net").unwrap();
+        assert_eq!(pair.0, Some(LevelFilter::Warn));
+        assert_eq!(pair.1.subsystem, "com.example.vendor");
+    }
 }
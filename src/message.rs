@@ -0,0 +1,285 @@
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::ffi::{CStr, CString};
+use std::fmt::{self, Write};
+
+/// A message passed to one of `OsLog`'s logging methods: a `&str`, a `&CStr`,
+/// which is logged without conversion when nothing needs to be added to it,
+/// or `format_args!`, which is formatted in to a stack buffer when it fits.
+#[derive(Debug, Clone, Copy)]
+pub enum LogMessage<'a> {
+    Str(&'a str),
+    CStr(&'a CStr),
+    Args(fmt::Arguments<'a>),
+}
+
+impl<'a> From<&'a str> for LogMessage<'a> {
+    fn from(message: &'a str) -> Self {
+        Self::Str(message)
+    }
+}
+
+impl<'a> From<&'a String> for LogMessage<'a> {
+    fn from(message: &'a String) -> Self {
+        Self::Str(message)
+    }
+}
+
+impl<'a, 'b> From<&'a Cow<'b, str>> for LogMessage<'a> {
+    fn from(message: &'a Cow<'b, str>) -> Self {
+        Self::Str(message)
+    }
+}
+
+impl<'a> From<&'a CStr> for LogMessage<'a> {
+    fn from(message: &'a CStr) -> Self {
+        Self::CStr(message)
+    }
+}
+
+impl<'a> From<&'a CString> for LogMessage<'a> {
+    fn from(message: &'a CString) -> Self {
+        Self::CStr(message)
+    }
+}
+
+impl<'a> From<fmt::Arguments<'a>> for LogMessage<'a> {
+    fn from(message: fmt::Arguments<'a>) -> Self {
+        Self::Args(message)
+    }
+}
+
+/// Buffers larger than this aren't kept for reuse.
+const MAX_REUSED_CAPACITY: usize = 64 * 1024;
+
+thread_local! {
+    /// Formatted messages too long for the stack buffer are written here, so
+    /// the allocation is reused by later messages on the same thread.
+    static HEAP_BUFFER: Cell<Vec<u8>> = const { Cell::new(Vec::new()) };
+}
+
+fn reuse(mut bytes: Vec<u8>) {
+    if bytes.capacity() <= MAX_REUSED_CAPACITY {
+        bytes.clear();
+        HEAP_BUFFER.with(|buffer| buffer.set(bytes));
+    }
+}
+
+/// A message as text, or as a C string if it's already NUL terminated.
+pub(crate) enum Resolved<'a> {
+    Str(Cow<'a, str>),
+    CStr(&'a CStr),
+    /// Formatted in to the thread's heap buffer, which it's returned to when
+    /// dropped.
+    Heap(CString),
+}
+
+impl Resolved<'_> {
+    pub(crate) fn to_str(&self) -> Cow<'_, str> {
+        match self {
+            Self::Str(message) => Cow::Borrowed(message),
+            Self::CStr(message) => message.to_string_lossy(),
+            Self::Heap(message) => message.to_string_lossy(),
+        }
+    }
+
+    pub(crate) fn as_cstr(&self) -> Option<&CStr> {
+        match self {
+            Self::Str(_) => None,
+            Self::CStr(message) => Some(message),
+            Self::Heap(message) => Some(message),
+        }
+    }
+}
+
+impl Drop for Resolved<'_> {
+    fn drop(&mut self) {
+        if let Self::Heap(message) = self {
+            reuse(std::mem::take(message).into_bytes_with_nul());
+        }
+    }
+}
+
+/// NUL terminates a message formatted on the heap in place, or returns it as
+/// text if it contains a NUL byte.
+fn terminate_on_heap(mut bytes: Vec<u8>) -> Resolved<'static> {
+    bytes.push(0);
+
+    match CString::from_vec_with_nul(bytes) {
+        Ok(message) => Resolved::Heap(message),
+        Err(error) => {
+            let mut bytes = error.into_bytes();
+            bytes.pop();
+            // Only ever written to with whole `str`s.
+            let message = String::from_utf8(bytes)
+                .unwrap_or_else(|error| String::from_utf8_lossy(error.as_bytes()).into_owned());
+            Resolved::Str(Cow::Owned(message))
+        }
+    }
+}
+
+/// The size of the buffer formatted messages are written to before falling
+/// back to the thread's heap buffer.
+pub(crate) const STACK_BUFFER_LENGTH: usize = 512;
+
+/// Holds formatted messages, NUL terminated, for `LogMessage::resolve`.
+pub(crate) struct StackBuffer {
+    bytes: [u8; STACK_BUFFER_LENGTH],
+    len: usize,
+    /// The thread's heap buffer, which formatting carries on in once `bytes`
+    /// is full.
+    spilled: Option<Vec<u8>>,
+}
+
+impl StackBuffer {
+    pub(crate) fn new() -> Self {
+        Self {
+            bytes: [0; STACK_BUFFER_LENGTH],
+            len: 0,
+            spilled: None,
+        }
+    }
+
+    /// Formats `args` once, then NUL terminates them wherever they ended up,
+    /// or returns them as text if they contain a NUL byte.
+    fn format(&mut self, args: fmt::Arguments) -> Resolved<'_> {
+        self.len = 0;
+        // Writing never fails, so this only stops early if a `Display` impl
+        // does, in which case what it managed to write is logged.
+        let _ = self.write_fmt(args);
+
+        let bytes = match self.spilled.take() {
+            Some(bytes) => bytes,
+            None if self.len < self.bytes.len() => {
+                self.bytes[self.len] = 0;
+                let message = &self.bytes[..=self.len];
+
+                return match CStr::from_bytes_with_nul(message) {
+                    Ok(message) => Resolved::CStr(message),
+                    Err(_) => Resolved::Str(String::from_utf8_lossy(&message[..self.len])),
+                };
+            }
+            None => {
+                let mut bytes = HEAP_BUFFER.with(Cell::take);
+                bytes.extend_from_slice(&self.bytes);
+                bytes
+            }
+        };
+
+        terminate_on_heap(bytes)
+    }
+}
+
+impl Write for StackBuffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if let Some(bytes) = &mut self.spilled {
+            bytes.extend_from_slice(s.as_bytes());
+            return Ok(());
+        }
+
+        let end = self.len + s.len();
+        if end > self.bytes.len() {
+            let mut bytes = HEAP_BUFFER.with(Cell::take);
+            bytes.extend_from_slice(&self.bytes[..self.len]);
+            bytes.extend_from_slice(s.as_bytes());
+            self.spilled = Some(bytes);
+            return Ok(());
+        }
+
+        self.bytes[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+impl<'a> LogMessage<'a> {
+    pub(crate) fn resolve(self, buffer: &'a mut StackBuffer) -> Resolved<'a> {
+        match self {
+            Self::Str(message) => Resolved::Str(Cow::Borrowed(message)),
+            Self::CStr(message) => Resolved::CStr(message),
+            Self::Args(args) => match args.as_str() {
+                Some(message) => Resolved::Str(Cow::Borrowed(message)),
+                None => buffer.format(args),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(message: LogMessage) -> String {
+        let mut buffer = StackBuffer::new();
+        let resolved = message.resolve(&mut buffer);
+        let kind = match resolved {
+            Resolved::Str(_) => "str",
+            Resolved::CStr(_) => "stack",
+            Resolved::Heap(_) => "heap",
+        };
+        format!("{} {}", kind, resolved.to_str())
+    }
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(resolve("Hello".into()), "str Hello");
+        assert_eq!(resolve(crate::cstr!("Hello").into()), "stack Hello");
+        assert_eq!(resolve(format_args!("Hello").into()), "str Hello");
+
+        let count = 1;
+        assert_eq!(
+            resolve(format_args!("Hello {}", count).into()),
+            "stack Hello 1"
+        );
+    }
+
+    #[test]
+    fn test_resolve_fallback() {
+        let long = "a".repeat(STACK_BUFFER_LENGTH);
+        assert_eq!(
+            resolve(format_args!("{}", long).into()),
+            format!("heap {}", long)
+        );
+        assert_eq!(resolve(format_args!("{}", "a\0b").into()), "str a\0b");
+        assert_eq!(
+            resolve(format_args!("{}{}", long, "\0").into()),
+            format!("str {}\0", long)
+        );
+
+        // Exactly fills the stack buffer, leaving no room for the NUL.
+        let full = "a".repeat(STACK_BUFFER_LENGTH - 1);
+        assert_eq!(
+            resolve(format_args!("{}b", full).into()),
+            format!("heap {}b", full)
+        );
+    }
+
+    #[test]
+    fn test_resolve_formats_once() {
+        struct Counted<'a>(&'a Cell<usize>);
+
+        impl fmt::Display for Counted<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.set(self.0.get() + 1);
+                f.write_str(&"a".repeat(STACK_BUFFER_LENGTH))
+            }
+        }
+
+        let count = Cell::new(0);
+        resolve(format_args!("{}", Counted(&count)).into());
+        assert_eq!(count.get(), 1);
+
+        resolve(format_args!("{}\0", Counted(&count)).into());
+        assert_eq!(count.get(), 2);
+    }
+
+    #[test]
+    fn test_heap_buffer_reused() {
+        let long = "a".repeat(STACK_BUFFER_LENGTH);
+        resolve(format_args!("{}", long).into());
+
+        let reused = HEAP_BUFFER.with(Cell::take);
+        assert!(reused.is_empty());
+        assert!(reused.capacity() > STACK_BUFFER_LENGTH);
+    }
+}
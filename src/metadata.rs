@@ -0,0 +1,176 @@
+use crate::context;
+use crate::event::format_value;
+use crate::{Level, OsLog};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Metadata attached to messages, like swift-log's `Logger.Metadata`. Keys
+/// are rendered in sorted order.
+pub type Metadata = BTreeMap<String, MetadataValue>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataValue {
+    String(String),
+    Int(i64),
+    Float(f64),
+    Array(Vec<MetadataValue>),
+    Dictionary(Metadata),
+}
+
+/// Renders nested values the way swift-log does, e.g. `[a, b]` and
+/// `[key: value]`.
+impl fmt::Display for MetadataValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(value) => write!(f, "{}", value),
+            Self::Int(value) => write!(f, "{}", value),
+            Self::Float(value) => write!(f, "{}", value),
+            Self::Array(values) => {
+                let values = values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+                write!(f, "[{}]", values.join(", "))
+            }
+            Self::Dictionary(metadata) => {
+                let entries = metadata
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", k, v))
+                    .collect::<Vec<_>>();
+                write!(f, "[{}]", entries.join(", "))
+            }
+        }
+    }
+}
+
+impl From<&str> for MetadataValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for MetadataValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+macro_rules! impl_from_int {
+    ($($int:ty),*) => {
+        $(
+            impl From<$int> for MetadataValue {
+                fn from(value: $int) -> Self {
+                    Self::Int(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_int!(i8, i16, i32, i64, u8, u16, u32);
+
+impl From<f32> for MetadataValue {
+    fn from(value: f32) -> Self {
+        Self::Float(value.into())
+    }
+}
+
+impl From<f64> for MetadataValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl<T: Into<MetadataValue>> From<Vec<T>> for MetadataValue {
+    fn from(values: Vec<T>) -> Self {
+        Self::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<Metadata> for MetadataValue {
+    fn from(metadata: Metadata) -> Self {
+        Self::Dictionary(metadata)
+    }
+}
+
+/// Renders `metadata` as `key=value` pairs separated by spaces.
+pub(crate) fn render(metadata: &Metadata) -> String {
+    metadata
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, format_value(&value.to_string())))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl OsLog {
+    /// Appends `metadata` to every message, after the message and before
+    /// the caller location.
+    pub fn metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Adds `key` to the metadata appended to every message.
+    pub fn metadata_value(mut self, key: &str, value: impl Into<MetadataValue>) -> Self {
+        self.metadata.insert(key.to_string(), value.into());
+        self
+    }
+
+    /// Logs `message` with `metadata`, the thread's context and the log's own
+    /// metadata, in that order of priority when they have the same key.
+    #[track_caller]
+    pub fn with_metadata(&self, level: Level, message: &str, metadata: &Metadata) {
+        let message = self.locate_with(message, &self.merged(metadata));
+        let _ = self.try_with_level_unlocated(level, &message);
+    }
+
+    pub(crate) fn merged(&self, metadata: &Metadata) -> Metadata {
+        let mut merged = self.metadata.clone();
+        merged.extend(context::fields());
+        merged.extend(metadata.iter().map(|(k, v)| (k.clone(), v.clone())));
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> Metadata {
+        let mut nested = Metadata::new();
+        nested.insert("b".into(), 2.into());
+        nested.insert("a".into(), "one".into());
+
+        let mut metadata = Metadata::new();
+        metadata.insert("request".into(), "GET /".into());
+        metadata.insert("attempt".into(), 3.into());
+        metadata.insert("ratio".into(), 0.5.into());
+        metadata.insert("tags".into(), vec!["x", "y"].into());
+        metadata.insert("nested".into(), nested.into());
+        metadata
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render(&metadata()),
+            "attempt=3 nested=\"[a: one, b: 2]\" ratio=0.5 request=\"GET /\" tags=\"[x, y]\""
+        );
+        assert_eq!(render(&Metadata::new()), "");
+    }
+
+    #[test]
+    fn test_metadata() {
+        let log = OsLog::new("com.example.oslog", "metadata")
+            .metadata_value("service", "api")
+            .metadata_value("attempt", 1);
+        assert_eq!(log.locate("Hi"), "Hi attempt=1 service=api");
+
+        let mut call = Metadata::new();
+        call.insert("attempt".into(), 2.into());
+        assert_eq!(
+            log.locate_with("Hi", &log.merged(&call)),
+            "Hi attempt=2 service=api"
+        );
+
+        log.with_metadata(Level::Info, "Hi", &call);
+        log.info("Hi");
+    }
+}
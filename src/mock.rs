@@ -0,0 +1,89 @@
+use crate::{Level, OsLogLike};
+use std::sync::Mutex;
+
+/// An in-memory stand-in for `OsLog` which records every message, so tests
+/// can assert on what was logged.
+#[derive(Debug, Default)]
+pub struct MockLog {
+    messages: Mutex<Vec<(Level, String)>>,
+}
+
+impl MockLog {
+    pub fn new() -> Self {
+        Self {
+            messages: Mutex::default(),
+        }
+    }
+
+    /// Returns the recorded messages, oldest first.
+    pub fn messages(&self) -> Vec<(Level, String)> {
+        self.messages.lock().unwrap().clone()
+    }
+
+    /// Returns true if a message at `level` containing `text` was recorded.
+    pub fn contains(&self, level: Level, text: &str) -> bool {
+        self.messages
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(l, message)| *l == level && message.contains(text))
+    }
+
+    pub fn clear(&self) {
+        self.messages.lock().unwrap().clear();
+    }
+}
+
+impl OsLogLike for MockLog {
+    fn with_level(&self, level: Level, message: &str) {
+        self.messages
+            .lock()
+            .unwrap()
+            .push((level, message.to_string()));
+    }
+
+    fn level_is_enabled(&self, _level: Level) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_everything(log: &impl OsLogLike) {
+        log.debug("Debug");
+        log.info("Info");
+        log.default("Default");
+        log.error("Error");
+        log.fault("Fault");
+    }
+
+    #[test]
+    fn test_records_messages() {
+        let log = MockLog::new();
+        log_everything(&log);
+
+        assert_eq!(
+            log.messages(),
+            vec![
+                (Level::Debug, "Debug".to_string()),
+                (Level::Info, "Info".to_string()),
+                (Level::Default, "Default".to_string()),
+                (Level::Error, "Error".to_string()),
+                (Level::Fault, "Fault".to_string()),
+            ]
+        );
+
+        assert!(log.contains(Level::Error, "Err"));
+        assert!(!log.contains(Level::Error, "Fault"));
+
+        log.clear();
+        assert!(log.messages().is_empty());
+    }
+
+    #[test]
+    fn test_os_log_is_like() {
+        log_everything(&crate::OsLog::new("com.example.oslog", "testing"));
+    }
+}
@@ -0,0 +1,253 @@
+use std::fmt;
+
+/// The maximum length in bytes of a subsystem or category name. Longer names
+/// are truncated by the system.
+pub const MAX_NAME_LENGTH: usize = 255;
+
+/// Categories the system gives special behaviour rather than treating as
+/// names: "PointsOfInterest" signposts are shown in Instruments' Points of
+/// Interest track, and "DynamicTracing" and "DynamicStackTracing" logs only
+/// emit signposts while Instruments is recording, the latter with
+/// backtraces. Use the constructors named after them to opt in.
+pub const RESERVED_CATEGORIES: &[&str] =
+    &["PointsOfInterest", "DynamicTracing", "DynamicStackTracing"];
+
+pub fn is_reserved_category(category: &str) -> bool {
+    RESERVED_CATEGORIES.contains(&category)
+}
+
+/// Why a subsystem or category name was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameError {
+    Empty,
+    TooLong {
+        length: usize,
+        max_length: usize,
+    },
+    /// The byte at `position` isn't allowed, e.g. a NUL or control
+    /// character.
+    InvalidCharacter {
+        position: usize,
+    },
+}
+
+impl fmt::Display for NameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "name is empty"),
+            Self::TooLong { length, max_length } => write!(
+                f,
+                "name is {} bytes long, the maximum is {}",
+                length, max_length
+            ),
+            Self::InvalidCharacter { position } => {
+                write!(
+                    f,
+                    "name contains an invalid character at position {}",
+                    position
+                )
+            }
+        }
+    }
+}
+
+const fn validate(name: &str, subsystem: bool) -> Result<(), NameError> {
+    let bytes = name.as_bytes();
+    if bytes.is_empty() {
+        return Err(NameError::Empty);
+    }
+    if bytes.len() > MAX_NAME_LENGTH {
+        return Err(NameError::TooLong {
+            length: bytes.len(),
+            max_length: MAX_NAME_LENGTH,
+        });
+    }
+
+    let mut position = 0;
+    while position < bytes.len() {
+        let byte = bytes[position];
+        let allowed = if subsystem {
+            subsystem_byte(byte)
+        } else {
+            category_byte(byte)
+        };
+        if !allowed {
+            return Err(NameError::InvalidCharacter { position });
+        }
+        position += 1;
+    }
+
+    Ok(())
+}
+
+const fn subsystem_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'.' | b'-' | b'_')
+}
+
+// Anything printable, including non-ASCII text and module paths.
+const fn category_byte(byte: u8) -> bool {
+    !byte.is_ascii_control()
+}
+
+/// Checks `subsystem` is a reverse DNS style identifier, e.g.
+/// "com.example.app", of ASCII letters, digits, '.', '-' and '_'.
+pub const fn validate_subsystem(subsystem: &str) -> Result<(), NameError> {
+    validate(subsystem, true)
+}
+
+/// Checks `category` isn't empty, too long, or contains control characters.
+pub const fn validate_category(category: &str) -> Result<(), NameError> {
+    validate(category, false)
+}
+
+/// A subsystem name which passed `validate_subsystem`.
+///
+/// ```
+/// use oslog::Subsystem;
+///
+/// const APP: Subsystem = Subsystem::new("com.example.app");
+/// assert!(Subsystem::try_new("com example").is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Subsystem<'a>(&'a str);
+
+impl<'a> Subsystem<'a> {
+    /// Panics if `name` is invalid, which fails compilation in a constant.
+    pub const fn new(name: &'a str) -> Self {
+        match Self::try_new(name) {
+            Ok(subsystem) => subsystem,
+            Err(_) => panic!("invalid subsystem name"),
+        }
+    }
+
+    pub const fn try_new(name: &'a str) -> Result<Self, NameError> {
+        match validate_subsystem(name) {
+            Ok(()) => Ok(Self(name)),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub const fn as_str(&self) -> &'a str {
+        self.0
+    }
+}
+
+/// A category name which passed `validate_category`. Reserved categories are
+/// allowed, and have their own constants.
+///
+/// ```
+/// use oslog::Category;
+///
+/// const NETWORK: Category = Category::new("Network");
+/// assert!(Category::try_new("").is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Category<'a>(&'a str);
+
+impl<'a> Category<'a> {
+    pub const POINTS_OF_INTEREST: Category<'static> = Category("PointsOfInterest");
+    pub const DYNAMIC_TRACING: Category<'static> = Category("DynamicTracing");
+    pub const DYNAMIC_STACK_TRACING: Category<'static> = Category("DynamicStackTracing");
+
+    /// Panics if `name` is invalid, which fails compilation in a constant.
+    pub const fn new(name: &'a str) -> Self {
+        match Self::try_new(name) {
+            Ok(category) => category,
+            Err(_) => panic!("invalid category name"),
+        }
+    }
+
+    pub const fn try_new(name: &'a str) -> Result<Self, NameError> {
+        match validate_category(name) {
+            Ok(()) => Ok(Self(name)),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub const fn as_str(&self) -> &'a str {
+        self.0
+    }
+}
+
+impl fmt::Display for Subsystem<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl fmt::Display for Category<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+/// Fails compilation of `define_log!` for invalid constant names.
+#[doc(hidden)]
+pub const fn __assert_valid_names(subsystem: &str, category: &str) {
+    if validate_subsystem(subsystem).is_err() {
+        panic!("invalid subsystem name");
+    }
+    if validate_category(category).is_err() {
+        panic!("invalid category name");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_subsystem() {
+        assert_eq!(validate_subsystem("com.example.app-beta_2"), Ok(()));
+        assert_eq!(validate_subsystem(""), Err(NameError::Empty));
+        assert_eq!(
+            validate_subsystem("com.example app"),
+            Err(NameError::InvalidCharacter { position: 11 })
+        );
+        assert_eq!(
+            validate_subsystem(&"a".repeat(256)),
+            Err(NameError::TooLong {
+                length: 256,
+                max_length: MAX_NAME_LENGTH
+            })
+        );
+    }
+
+    #[test]
+    fn test_newtypes() {
+        const SUBSYSTEM: Subsystem = Subsystem::new("com.example.app");
+        assert_eq!(SUBSYSTEM.as_str(), "com.example.app");
+        assert_eq!(
+            Subsystem::try_new("com example"),
+            Err(NameError::InvalidCharacter { position: 3 })
+        );
+
+        assert_eq!(Category::new("Network").to_string(), "Network");
+        assert_eq!(Category::try_new(""), Err(NameError::Empty));
+        assert!(is_reserved_category(
+            Category::DYNAMIC_STACK_TRACING.as_str()
+        ));
+    }
+
+    #[test]
+    fn test_is_reserved_category() {
+        assert!(is_reserved_category("PointsOfInterest"));
+        assert!(is_reserved_category("DynamicStackTracing"));
+        assert!(!is_reserved_category("pointsofinterest"));
+        assert!(!is_reserved_category("Networking"));
+    }
+
+    #[test]
+    fn test_validate_category() {
+        assert_eq!(validate_category("hyper::proto"), Ok(()));
+        assert_eq!(validate_category("Réseau"), Ok(()));
+        assert_eq!(
+            validate_category("Data\0base"),
+            Err(NameError::InvalidCharacter { position: 4 })
+        );
+        assert_eq!(
+            validate_category("Line\nbreak"),
+            Err(NameError::InvalidCharacter { position: 4 })
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use crate::Error;
+use std::ffi::CString;
+
+/// What to do with NUL bytes in messages, which can't be passed to the OS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NulBytes {
+    /// Remove them.
+    Strip,
+    /// Replace each of them with the marker.
+    Replace(&'static str),
+    /// Don't log the message at all.
+    Error,
+}
+
+impl Default for NulBytes {
+    fn default() -> Self {
+        Self::Replace("(null)")
+    }
+}
+
+impl NulBytes {
+    /// Converts `message`, which is known to contain a NUL byte at `position`.
+    pub(crate) fn apply(self, message: &str, position: usize) -> Result<CString, Error> {
+        let fixed = match self {
+            Self::Strip => message.replace('\0', ""),
+            Self::Replace(marker) => message.replace('\0', marker),
+            Self::Error => return Err(Error::InteriorNul { position }),
+        };
+
+        // The marker could contain a NUL byte itself.
+        CString::new(fixed).map_err(|e| Error::InteriorNul {
+            position: e.nul_position(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip() {
+        assert_eq!(
+            NulBytes::Strip.apply("a\0b\0", 1).unwrap().as_bytes(),
+            b"ab"
+        );
+    }
+
+    #[test]
+    fn test_replace() {
+        assert_eq!(
+            NulBytes::default().apply("a\0b", 1).unwrap().as_bytes(),
+            b"a(null)b"
+        );
+        assert_eq!(
+            NulBytes::Replace("?").apply("a\0b", 1).unwrap().as_bytes(),
+            b"a?b"
+        );
+    }
+
+    #[test]
+    fn test_replace_with_nul_marker() {
+        assert_eq!(
+            NulBytes::Replace("\0").apply("a\0b", 1),
+            Err(Error::InteriorNul { position: 1 })
+        );
+    }
+
+    #[test]
+    fn test_error() {
+        assert_eq!(
+            NulBytes::Error.apply("ab\0", 2),
+            Err(Error::InteriorNul { position: 2 })
+        );
+    }
+}
@@ -0,0 +1,59 @@
+//! Conversions to and from the objc2 crates' types, for apps which already
+//! use them.
+
+use crate::{Level, OsLog};
+use objc2::rc::Retained;
+use objc2::runtime::NSObject;
+use objc2_foundation::NSString;
+
+impl OsLog {
+    /// Creates a log from names held as `NSString`s.
+    pub fn from_ns_strings(subsystem: &NSString, category: &NSString) -> Self {
+        Self::new(&subsystem.to_string(), &category.to_string())
+    }
+
+    /// Wraps an `OSLog` object, retaining it. See `from_swift_handle` for
+    /// why the names are needed.
+    pub fn from_object(object: &NSObject, subsystem: &str, category: &str) -> Self {
+        // An `NSObject` reference is always a live object, and os_log_t is
+        // one when compiled as Objective-C.
+        unsafe {
+            Self::from_swift_handle(
+                object as *const NSObject as *mut std::ffi::c_void,
+                subsystem,
+                category,
+            )
+        }
+    }
+
+    /// Returns the underlying `OSLog` object, retained, e.g. to hand to
+    /// Objective-C or Swift code.
+    pub fn to_object(&self) -> Retained<NSObject> {
+        unsafe { Retained::retain(self.inner as *mut NSObject) }.expect("Unexpected null log")
+    }
+
+    /// Logs `message` at `level`.
+    pub fn log_ns_string(&self, level: Level, message: &NSString) {
+        self.with_level(level, &message.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ns_strings() {
+        let log = OsLog::from_ns_strings(
+            &NSString::from_str("com.example.oslog"),
+            &NSString::from_str("objc2"),
+        );
+        log.log_ns_string(Level::Default, &NSString::from_str("Hello"));
+
+        let object = log.to_object();
+        let other = OsLog::from_object(&object, "com.example.oslog", "objc2");
+        assert!(log == other);
+        drop(log);
+        other.default("Hello");
+    }
+}
@@ -0,0 +1,136 @@
+//! Shows OpenTelemetry spans in Instruments.
+//!
+//! ```no_run
+//! use opentelemetry::trace::{Tracer, TracerProvider};
+//! use opentelemetry_sdk::trace::SdkTracerProvider;
+//! use oslog::{otel::SignpostProcessor, OsLog};
+//!
+//! let provider = SdkTracerProvider::builder()
+//!     .with_span_processor(SignpostProcessor::new(OsLog::new("com.example.test", "Spans")))
+//!     .build();
+//!
+//! provider.tracer("example").in_span("handle request", |_| {
+//!     // ...
+//! });
+//! ```
+
+use crate::signpost::SignpostId;
+use crate::OsLog;
+use opentelemetry::trace::{SpanId, Status};
+use opentelemetry::Context;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{Span, SpanData, SpanProcessor};
+use std::fmt;
+use std::time::{Duration, Instant, SystemTime};
+
+/// A span processor which emits a signpost interval for each span when it
+/// ends, named after the span and identified by its span ID.
+///
+/// Spans without a valid ID would all share one signpost ID, but overlapping
+/// intervals need IDs of their own, as described on `SignpostId`, so they're
+/// skipped. It can be registered alongside any exporting processors.
+pub struct SignpostProcessor {
+    log: OsLog,
+}
+
+impl SignpostProcessor {
+    pub fn new(log: OsLog) -> Self {
+        Self { log }
+    }
+}
+
+impl fmt::Debug for SignpostProcessor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignpostProcessor")
+            .field("subsystem", &self.log.subsystem)
+            .field("category", &self.log.category)
+            .finish()
+    }
+}
+
+fn signpost_id(span_id: SpanId) -> Option<SignpostId> {
+    let id = SignpostId::from_value(u64::from_be_bytes(span_id.to_bytes()));
+    (id != SignpostId::EXCLUSIVE).then_some(id)
+}
+
+/// The instant `time` corresponds to, given the clocks' readings at `now`.
+fn instant(time: SystemTime, now: (SystemTime, Instant)) -> Instant {
+    match now.0.duration_since(time) {
+        Ok(ago) => now.1.checked_sub(ago).unwrap_or(now.1),
+        Err(error) => now.1 + error.duration(),
+    }
+}
+
+impl SpanProcessor for SignpostProcessor {
+    fn on_start(&self, _span: &mut Span, _cx: &Context) {}
+
+    fn on_end(&self, span: SpanData) {
+        if !self.log.signposts_enabled() {
+            return;
+        }
+
+        let id = match signpost_id(span.span_context.span_id()) {
+            Some(id) => id,
+            None => return,
+        };
+        let message = match span.status {
+            Status::Error { description } => format!("error: {}", description),
+            _ => String::new(),
+        };
+
+        let now = (SystemTime::now(), Instant::now());
+        let (start, end) = (instant(span.start_time, now), instant(span.end_time, now));
+        self.log
+            .signpost_interval_at(id, &span.name, &message, start, end);
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        Ok(())
+    }
+
+    fn shutdown_with_timeout(&self, _timeout: Duration) -> OTelSdkResult {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::{TraceContextExt, Tracer, TracerProvider};
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+
+    #[test]
+    fn test_signpost_id() {
+        let span_id = SpanId::from_bytes([0, 0, 0, 0, 0, 0, 1, 0]);
+        assert_eq!(signpost_id(span_id).map(SignpostId::as_u64), Some(256));
+        assert_eq!(signpost_id(SpanId::INVALID), None);
+    }
+
+    #[test]
+    fn test_instant() {
+        let now = (SystemTime::now(), Instant::now());
+        let second = Duration::from_secs(1);
+        assert_eq!(instant(now.0, now), now.1);
+        assert_eq!(instant(now.0 - second, now), now.1 - second);
+        assert_eq!(instant(now.0 + second, now), now.1 + second);
+    }
+
+    #[test]
+    fn test_spans() {
+        let provider = SdkTracerProvider::builder()
+            .with_span_processor(SignpostProcessor::new(OsLog::new(
+                "com.example.oslog",
+                "spans",
+            )))
+            .build();
+
+        let tracer = provider.tracer("oslog");
+        tracer.in_span("outer", |_| {
+            tracer.in_span("inner", |cx| {
+                cx.span().set_status(Status::error("failed"));
+            });
+        });
+
+        provider.shutdown().unwrap();
+    }
+}
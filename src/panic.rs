@@ -0,0 +1,76 @@
+use crate::OsLog;
+use std::any::Any;
+use std::panic::{self, Location};
+use std::thread;
+
+fn describe(payload: &(dyn Any + Send), location: Option<&Location<'_>>) -> String {
+    let message = if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.as_str()
+    } else {
+        "Box<dyn Any>"
+    };
+
+    let thread = thread::current();
+    let thread = thread.name().unwrap_or("<unnamed>");
+
+    match location {
+        Some(location) => format!(
+            "thread '{}' panicked at {}:{}:{}: {}",
+            thread,
+            location.file(),
+            location.line(),
+            location.column(),
+            message
+        ),
+        None => format!("thread '{}' panicked: {}", thread, message),
+    }
+}
+
+/// Registers a panic hook which logs the panic message, location and thread
+/// name to `log` at the fault level, then calls the previously registered
+/// hook.
+pub fn install_panic_hook(log: OsLog) {
+    let previous = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        log.fault(&describe(info.payload(), info.location()));
+        previous(info);
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        let location = Location::caller();
+        let payload: Box<dyn Any + Send> = Box::new("Oops");
+
+        let description = describe(&*payload, Some(location));
+        assert!(description.starts_with("thread '"));
+        assert!(description.contains(&format!("{}:{}:", file!(), location.line())));
+        assert!(description.ends_with(": Oops"));
+
+        let payload: Box<dyn Any + Send> = Box::new(String::from("Oops"));
+        assert!(describe(&*payload, None).ends_with("panicked: Oops"));
+
+        let payload: Box<dyn Any + Send> = Box::new(1);
+        assert!(describe(&*payload, None).ends_with("Box<dyn Any>"));
+    }
+
+    #[test]
+    fn test_install_panic_hook() {
+        install_panic_hook(OsLog::new("com.example.oslog", "panic"));
+
+        let result = thread::Builder::new()
+            .name("panicking".into())
+            .spawn(|| panic!("Oops"))
+            .unwrap()
+            .join();
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,59 @@
+//! Measuring the crate's own overhead. While enabled, every call in to
+//! os_log, and every flush of an `OsLogWriter`, is wrapped in a signpost
+//! interval in the `oslog` subsystem, so Instruments shows how much time
+//! logging takes on each thread.
+
+use crate::{LazyLog, SignpostId};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The subsystem the crate's own signposts are emitted to.
+pub const SUBSYSTEM: &str = "oslog";
+
+/// The category the crate's own signposts are emitted to.
+pub const CATEGORY: &str = "Overhead";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static LOG: LazyLog = LazyLog::new(SUBSYSTEM, CATEGORY);
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::SeqCst);
+}
+
+pub fn disable() {
+    ENABLED.store(false, Ordering::SeqCst);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Calls `f` in an interval named `name` while profiling is enabled.
+pub(crate) fn measure<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    if !is_enabled() || !LOG.signposts_enabled() {
+        return f();
+    }
+
+    let id = SignpostId::generate(&LOG);
+    LOG.signpost_interval_begin(id, name, "");
+    let result = f();
+    LOG.signpost_interval_end(id, name, "");
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure() {
+        assert_eq!(measure("Disabled", || 1), 1);
+
+        enable();
+        assert!(is_enabled());
+        assert_eq!(measure("Enabled", || 2), 2);
+
+        disable();
+        assert!(!is_enabled());
+    }
+}
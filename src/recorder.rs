@@ -0,0 +1,226 @@
+//! Charting metrics from the `metrics` crate in Instruments.
+
+use crate::signpost::SignpostId;
+use crate::sys::*;
+use crate::{to_cstr, Error, NulBytes, OsLog};
+use metrics::{
+    Counter, CounterFn, Gauge, GaugeFn, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder,
+    SharedString, Unit,
+};
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// The category metrics are logged to.
+pub const CATEGORY: &str = "Metrics";
+
+type Emit = unsafe extern "C" fn(os_log_t, os_signpost_id_t, *const c_char, f64);
+
+/// A `metrics::Recorder` which emits a signpost event named "Counter",
+/// "Gauge" or "Histogram" each time a metric changes, with the key and value
+/// as separate arguments. Counters and gauges report their current value
+/// rather than the change.
+///
+/// ```no_run
+/// oslog::recorder::SignpostRecorder::new("com.example.test")
+///     .install()
+///     .unwrap();
+///
+/// metrics::counter!("requests", "method" => "GET").increment(1);
+/// ```
+pub struct SignpostRecorder {
+    log: Arc<OsLog>,
+    counters: Mutex<HashMap<Key, Arc<Metric>>>,
+    gauges: Mutex<HashMap<Key, Arc<Metric>>>,
+    histograms: Mutex<HashMap<Key, Arc<Metric>>>,
+}
+
+impl SignpostRecorder {
+    pub fn new(subsystem: &str) -> Self {
+        Self {
+            log: Arc::new(OsLog::new(subsystem, CATEGORY)),
+            counters: Mutex::default(),
+            gauges: Mutex::default(),
+            histograms: Mutex::default(),
+        }
+    }
+
+    /// Sets this as the global recorder.
+    pub fn install(self) -> Result<(), Error> {
+        metrics::set_global_recorder(self).map_err(|_| Error::AlreadyInstalled)
+    }
+
+    fn register(
+        &self,
+        metrics: &Mutex<HashMap<Key, Arc<Metric>>>,
+        key: &Key,
+        emit: Emit,
+    ) -> Arc<Metric> {
+        metrics
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_insert_with(|| {
+                Arc::new(Metric {
+                    log: self.log.clone(),
+                    id: SignpostId::generate(&self.log),
+                    key: format_key(key),
+                    value: AtomicU64::new(0),
+                    emit,
+                })
+            })
+            .clone()
+    }
+}
+
+/// Formats `key` the way Prometheus does, e.g. `requests{method=GET}`.
+fn format_key(key: &Key) -> CString {
+    let mut formatted = key.name().to_string();
+    let mut labels = key.labels().peekable();
+
+    if labels.peek().is_some() {
+        let labels = labels
+            .map(|label| format!("{}={}", label.key(), label.value()))
+            .collect::<Vec<_>>();
+        formatted = format!("{}{{{}}}", formatted, labels.join(","));
+    }
+
+    to_cstr(&formatted, NulBytes::default()).unwrap()
+}
+
+impl Recorder for SignpostRecorder {
+    fn describe_counter(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn describe_gauge(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn describe_histogram(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _metadata: &Metadata<'_>) -> Counter {
+        Counter::from_arc(self.register(&self.counters, key, wrapped_os_signpost_counter))
+    }
+
+    fn register_gauge(&self, key: &Key, _metadata: &Metadata<'_>) -> Gauge {
+        Gauge::from_arc(self.register(&self.gauges, key, wrapped_os_signpost_gauge))
+    }
+
+    fn register_histogram(&self, key: &Key, _metadata: &Metadata<'_>) -> Histogram {
+        Histogram::from_arc(self.register(&self.histograms, key, wrapped_os_signpost_histogram))
+    }
+}
+
+/// A registered metric. Counters store their total as an integer, gauges
+/// store the bits of an `f64`, and histograms don't use `value`.
+struct Metric {
+    log: Arc<OsLog>,
+    id: SignpostId,
+    key: CString,
+    value: AtomicU64,
+    emit: Emit,
+}
+
+impl Metric {
+    fn emit(&self, value: f64) {
+        if self.log.signposts_enabled() {
+            unsafe { (self.emit)(self.log.inner, self.id.as_u64(), self.key.as_ptr(), value) }
+        }
+    }
+
+    fn update_gauge(&self, update: impl Fn(f64) -> f64) {
+        let previous = self
+            .value
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                Some(update(f64::from_bits(bits)).to_bits())
+            })
+            .unwrap();
+        self.emit(update(f64::from_bits(previous)));
+    }
+}
+
+impl CounterFn for Metric {
+    fn increment(&self, value: u64) {
+        let total = self.value.fetch_add(value, Ordering::Relaxed) + value;
+        self.emit(total as f64);
+    }
+
+    fn absolute(&self, value: u64) {
+        let total = self.value.fetch_max(value, Ordering::Relaxed).max(value);
+        self.emit(total as f64);
+    }
+}
+
+impl GaugeFn for Metric {
+    fn increment(&self, value: f64) {
+        self.update_gauge(|current| current + value);
+    }
+
+    fn decrement(&self, value: f64) {
+        self.update_gauge(|current| current - value);
+    }
+
+    fn set(&self, value: f64) {
+        self.value.store(value.to_bits(), Ordering::Relaxed);
+        self.emit(value);
+    }
+}
+
+impl HistogramFn for Metric {
+    fn record(&self, value: f64) {
+        self.emit(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(metrics: &Mutex<HashMap<Key, Arc<Metric>>>, name: &'static str) -> u64 {
+        metrics.lock().unwrap()[&Key::from_name(name)]
+            .value
+            .load(Ordering::Relaxed)
+    }
+
+    #[test]
+    fn test_format_key() {
+        assert_eq!(
+            format_key(&Key::from_name("requests")).to_str(),
+            Ok("requests")
+        );
+
+        let key = Key::from_parts(
+            "requests",
+            vec![
+                metrics::Label::new("method", "GET"),
+                metrics::Label::new("status", "200"),
+            ],
+        );
+        assert_eq!(
+            format_key(&key).to_str(),
+            Ok("requests{method=GET,status=200}")
+        );
+    }
+
+    #[test]
+    fn test_recorder() {
+        let recorder = SignpostRecorder::new("com.example.oslog");
+
+        metrics::with_local_recorder(&recorder, || {
+            metrics::counter!("requests").increment(2);
+            metrics::counter!("requests").increment(3);
+            metrics::counter!("bytes").absolute(10);
+            metrics::counter!("bytes").absolute(5);
+
+            metrics::gauge!("connections").set(4.0);
+            metrics::gauge!("connections").increment(2.0);
+            metrics::gauge!("connections").decrement(1.5);
+
+            metrics::histogram!("latency").record(0.25);
+        });
+
+        assert_eq!(value(&recorder.counters, "requests"), 5);
+        assert_eq!(value(&recorder.counters, "bytes"), 10);
+        assert_eq!(f64::from_bits(value(&recorder.gauges, "connections")), 4.5);
+        assert_eq!(recorder.histograms.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,95 @@
+//! Logging error reports from anyhow and eyre.
+
+use crate::{ext::format_error_chain, Error, OsLog};
+use std::fmt;
+use std::sync::OnceLock;
+
+static LOG: OnceLock<OsLog> = OnceLock::new();
+
+/// Sets the log reports are written to at the error level. With the `eyre`
+/// feature enabled an eyre hook is also installed, so every `eyre::Report`
+/// formatted with `{:?}`, including the one printed when `main` returns an
+/// error, is logged.
+pub fn install(log: OsLog) -> Result<(), Error> {
+    // The hook goes first, so the log isn't set if another hook was already
+    // installed.
+    #[cfg(feature = "eyre")]
+    eyre::set_hook(Box::new(|error| {
+        Box::new(EyreHandler {
+            inner: eyre::DefaultHandler::default_with(error),
+        })
+    }))
+    .map_err(|_| Error::AlreadyInstalled)?;
+
+    LOG.set(log).map_err(|_| Error::AlreadyInstalled)
+}
+
+fn log_chain(error: &dyn std::error::Error) {
+    if let Some(log) = LOG.get() {
+        log.error(&format_error_chain(error));
+    }
+}
+
+#[cfg(feature = "eyre")]
+struct EyreHandler {
+    inner: Box<dyn eyre::EyreHandler>,
+}
+
+#[cfg(feature = "eyre")]
+impl eyre::EyreHandler for EyreHandler {
+    fn debug(
+        &self,
+        error: &(dyn std::error::Error + 'static),
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        log_chain(error);
+        self.inner.debug(error, f)
+    }
+
+    fn display(
+        &self,
+        error: &(dyn std::error::Error + 'static),
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        self.inner.display(error, f)
+    }
+
+    fn track_caller(&mut self, location: &'static std::panic::Location<'static>) {
+        self.inner.track_caller(location);
+    }
+}
+
+/// Wraps an `anyhow::Error` so it's logged when formatted with `{:?}`,
+/// which is what happens when it's returned from `main`:
+///
+/// ```no_run
+/// fn main() -> Result<(), oslog::report::Report> {
+///     oslog::report::install(oslog::OsLog::new("com.example", "Errors")).unwrap();
+///     std::fs::read("missing")?;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "anyhow")]
+pub struct Report(pub anyhow::Error);
+
+#[cfg(feature = "anyhow")]
+impl<E: Into<anyhow::Error>> From<E> for Report {
+    fn from(error: E) -> Self {
+        Self(error.into())
+    }
+}
+
+#[cfg(feature = "anyhow")]
+impl fmt::Debug for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        log_chain(&*self.0);
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+#[cfg(feature = "anyhow")]
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
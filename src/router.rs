@@ -0,0 +1,89 @@
+use crate::{Level, OsLog, OsLogLike};
+
+/// Sends some levels to other categories, e.g. errors and faults to an
+/// "Errors" category, since categories are the main way to filter in
+/// Console.
+///
+/// ```no_run
+/// # use oslog::{Level, LevelRouter, OsLog, OsLogLike};
+/// let log = LevelRouter::new(OsLog::new("com.example.test", "Parsing"))
+///     .route(Level::Error, "Errors")
+///     .route(Level::Fault, "Errors");
+///
+/// log.info("Parsing settings");
+/// log.error("Unexpected token");
+/// ```
+pub struct LevelRouter {
+    log: OsLog,
+    routes: Vec<(Level, OsLog)>,
+}
+
+impl LevelRouter {
+    /// Messages at levels without a route go to `log`.
+    pub fn new(log: OsLog) -> Self {
+        Self {
+            log,
+            routes: Vec::new(),
+        }
+    }
+
+    /// Sends messages at `level` to `category`, in the same subsystem and
+    /// with the same settings as the default log. Replaces any existing
+    /// route for `level`.
+    pub fn route(mut self, level: Level, category: &str) -> Self {
+        let log = self.log.sibling(category);
+
+        match self.routes.iter_mut().find(|(l, _)| *l == level) {
+            Some(route) => route.1 = log,
+            None => self.routes.push((level, log)),
+        }
+
+        self
+    }
+
+    pub fn log_for(&self, level: Level) -> &OsLog {
+        self.routes
+            .iter()
+            .find(|(l, _)| *l == level)
+            .map_or(&self.log, |(_, log)| log)
+    }
+}
+
+impl OsLogLike for LevelRouter {
+    #[track_caller]
+    fn with_level(&self, level: Level, message: &str) {
+        self.log_for(level).with_level(level, message);
+    }
+
+    fn level_is_enabled(&self, level: Level) -> bool {
+        self.log_for(level).level_is_enabled(level)
+    }
+
+    #[track_caller]
+    fn fault(&self, message: &str) {
+        self.log_for(Level::Fault).fault(message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route() {
+        let router = LevelRouter::new(OsLog::new("com.example.oslog", "router"))
+            .route(Level::Error, "Errors")
+            .route(Level::Fault, "Faults")
+            .route(Level::Fault, "Errors");
+
+        assert_eq!(router.log_for(Level::Info).category, "router");
+        assert_eq!(router.log_for(Level::Error).category, "Errors");
+        assert_eq!(router.log_for(Level::Fault).category, "Errors");
+        assert_eq!(router.log_for(Level::Fault).subsystem, "com.example.oslog");
+        assert_eq!(router.routes.len(), 2);
+
+        router.info("Info");
+        router.error("Error");
+        router.fault("Fault");
+    }
+}
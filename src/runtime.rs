@@ -0,0 +1,182 @@
+//! Showing tokio's scheduling in Instruments.
+
+use crate::signpost::{SignpostId, SignpostInterval};
+use crate::OsLog;
+use std::cell::Cell;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::runtime::Builder;
+use tokio::task::futures::TaskLocalFuture;
+
+tokio::task_local! {
+    static TASK_ID: SignpostId;
+}
+
+thread_local! {
+    /// Worker threads park and poll one thing at a time, so each needs only
+    /// one ID to keep its intervals apart from other threads'.
+    static THREAD_ID: Cell<Option<SignpostId>> = const { Cell::new(None) };
+}
+
+fn thread_id(log: &OsLog) -> SignpostId {
+    THREAD_ID.with(|id| {
+        id.get().unwrap_or_else(|| {
+            let generated = SignpostId::generate(log);
+            id.set(Some(generated));
+            generated
+        })
+    })
+}
+
+/// Adds hooks to `builder` which mark the time each worker thread spends
+/// parked with a "Park" interval.
+///
+/// When built with `--cfg tokio_unstable` each task poll is also marked with
+/// a "Poll" interval, and task spawns and terminations with "Spawn" and
+/// "Terminate" events, with the task ID in the message.
+///
+/// ```no_run
+/// let runtime = oslog::runtime::instrument(
+///     &mut tokio::runtime::Builder::new_current_thread(),
+///     oslog::OsLog::new("com.example.test", "Runtime"),
+/// )
+/// .build()
+/// .unwrap();
+/// ```
+pub fn instrument(builder: &mut Builder, log: OsLog) -> &mut Builder {
+    let log = Arc::new(log);
+
+    let park = log.clone();
+    let unpark = log.clone();
+    builder
+        .on_thread_park(move || park.signpost_interval_begin(thread_id(&park), "Park", ""))
+        .on_thread_unpark(move || unpark.signpost_interval_end(thread_id(&unpark), "Park", ""));
+
+    #[cfg(tokio_unstable)]
+    {
+        let spawn = log.clone();
+        let terminate = log.clone();
+        let before_poll = log.clone();
+        let after_poll = log;
+        builder
+            .on_task_spawn(move |task| {
+                if spawn.signposts_enabled() {
+                    let location = task.spawned_at();
+                    let message = format!(
+                        "task {} at {}:{}",
+                        task.id(),
+                        location.file(),
+                        location.line()
+                    );
+                    spawn.signpost_event(thread_id(&spawn), "Spawn", &message);
+                }
+            })
+            .on_task_terminate(move |task| {
+                if terminate.signposts_enabled() {
+                    let message = format!("task {}", task.id());
+                    terminate.signpost_event(thread_id(&terminate), "Terminate", &message);
+                }
+            })
+            .on_before_task_poll(move |task| {
+                if before_poll.signposts_enabled() {
+                    let message = format!("task {}", task.id());
+                    before_poll.signpost_interval_begin(thread_id(&before_poll), "Poll", &message);
+                }
+            })
+            .on_after_task_poll(move |_| {
+                if after_poll.signposts_enabled() {
+                    after_poll.signpost_interval_end(thread_id(&after_poll), "Poll", "");
+                }
+            });
+    }
+
+    builder
+}
+
+/// Runs `future` with a new ID from `log`, which `signpost_interval` begins
+/// every interval within it with, so concurrent requests can be told apart
+/// in Instruments.
+///
+/// ```no_run
+/// # async fn handle() {}
+/// oslog::define_log!(LOG, "com.example.test", "Requests");
+///
+/// # async fn run() {
+/// tokio::spawn(oslog::runtime::with_signpost_id(&LOG, async {
+///     let _interval = oslog::runtime::signpost_interval(&LOG, "Request");
+///     handle().await;
+/// }));
+/// # }
+/// ```
+pub fn with_signpost_id<F: Future>(log: &OsLog, future: F) -> TaskLocalFuture<SignpostId, F> {
+    TASK_ID.scope(SignpostId::generate(log), future)
+}
+
+/// The ID set by the enclosing `with_signpost_id`, if any.
+pub fn signpost_id() -> Option<SignpostId> {
+    TASK_ID.try_with(|id| *id).ok()
+}
+
+/// Begins an interval with the enclosing `with_signpost_id`'s ID, or a new
+/// one outside it, which ends when the returned guard is dropped.
+///
+/// A task's intervals share its ID, so they must nest, as described on
+/// `SignpostId`.
+pub fn signpost_interval<'a>(log: &'a OsLog, name: &'a str) -> SignpostInterval<'a> {
+    match signpost_id() {
+        Some(id) => log.signpost_interval_with_id(id, name),
+        None => log.signpost_interval(name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instrument() {
+        let runtime = instrument(
+            &mut Builder::new_current_thread(),
+            OsLog::new("com.example.oslog", "runtime"),
+        )
+        .build()
+        .unwrap();
+
+        let result = runtime.block_on(async {
+            let task = tokio::spawn(async {
+                tokio::task::yield_now().await;
+                1
+            });
+            task.await.unwrap()
+        });
+
+        assert_eq!(result, 1);
+    }
+
+    #[test]
+    fn test_with_signpost_id() {
+        let runtime = Builder::new_current_thread().build().unwrap();
+        let log = OsLog::new("com.example.oslog", "runtime");
+        assert_eq!(signpost_id(), None);
+
+        let ids = runtime.block_on(async {
+            let first = tokio::spawn(with_signpost_id(&log, async {
+                tokio::task::yield_now().await;
+                signpost_id()
+            }));
+            let second = tokio::spawn(with_signpost_id(&log, async { signpost_id() }));
+            (first.await.unwrap(), second.await.unwrap())
+        });
+
+        assert!(ids.0.is_some());
+        assert!(ids.1.is_some());
+
+        runtime.block_on(with_signpost_id(&log, async {
+            let outer = signpost_interval(&log, "Request");
+            let inner = signpost_interval(&log, "Query");
+
+            assert_eq!(Some(outer.id()), signpost_id());
+            assert_eq!(inner.id(), outer.id());
+        }));
+    }
+}
@@ -0,0 +1,86 @@
+//! "process started" and "process exiting" messages marking where each run
+//! of the program begins and ends, for finding sessions in Console.
+
+use crate::event::format_value;
+use crate::{Level, OsLog};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// The category session markers are logged to, in the logger's subsystem.
+pub const SESSION_CATEGORY: &str = "Session";
+
+pub(crate) struct SessionMarkers {
+    pub(crate) version: String,
+    pub(crate) build: String,
+}
+
+static SESSION: OnceLock<(OsLog, Instant)> = OnceLock::new();
+
+/// Hashes the arguments, so runs with different arguments can be told apart
+/// without logging them.
+fn args_hash() -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for arg in std::env::args_os() {
+        arg.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+fn started_message(markers: &SessionMarkers, args_hash: u64) -> String {
+    format!(
+        "process started version={} build={} args_hash={:016x}",
+        format_value(&markers.version),
+        format_value(&markers.build),
+        args_hash
+    )
+}
+
+fn exiting_message(uptime: Duration) -> String {
+    format!("process exiting uptime={:.3}s", uptime.as_secs_f64())
+}
+
+extern "C" fn log_exit() {
+    if let Some((log, started)) = SESSION.get() {
+        log.with_level(Level::Default, &exiting_message(started.elapsed()));
+    }
+}
+
+/// Logs the start marker, and the exit marker when the process exits
+/// normally. Only the first call does anything.
+pub(crate) fn start(log: OsLog, markers: &SessionMarkers) {
+    if SESSION.set((log, Instant::now())).is_err() {
+        return;
+    }
+
+    let (log, _) = SESSION.get().unwrap();
+    log.with_level(Level::Default, &started_message(markers, args_hash()));
+
+    unsafe {
+        libc::atexit(log_exit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages() {
+        let markers = SessionMarkers {
+            version: "1.2.0".into(),
+            build: "nightly 42".into(),
+        };
+
+        assert_eq!(
+            started_message(&markers, 0xabc),
+            "process started version=1.2.0 build=\"nightly 42\" args_hash=0000000000000abc"
+        );
+        assert_eq!(
+            exiting_message(Duration::from_millis(1500)),
+            "process exiting uptime=1.500s"
+        );
+        assert_eq!(args_hash(), args_hash());
+    }
+}
@@ -0,0 +1,268 @@
+use crate::sys::*;
+use crate::{to_cstr, OsLog};
+use std::ffi::CStr;
+use std::time::{Duration, Instant};
+
+/// Pairs the beginning and end of an interval, and distinguishes overlapping
+/// intervals logged to the same log.
+///
+/// os_signpost needs literal names, so every interval this crate emits is
+/// named "Interval" as far as the system is concerned, with the caller's
+/// name in its message, and its beginning and end are paired by ID alone.
+/// Intervals sharing an ID must therefore nest, and are paired innermost
+/// first; intervals which overlap need IDs of their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SignpostId(os_signpost_id_t);
+
+impl SignpostId {
+    /// For intervals which never overlap within a log, so don't need a
+    /// unique ID.
+    pub const EXCLUSIVE: Self = Self(OS_SIGNPOST_ID_EXCLUSIVE);
+
+    /// Generates an ID which is unique within `log`.
+    pub fn generate(log: &OsLog) -> Self {
+        Self(unsafe { os_signpost_id_generate(log.inner) })
+    }
+
+    /// Uses an application identifier, e.g. a request or job ID, directly,
+    /// so intervals can be matched up with the application's own records.
+    /// Unlike generated IDs these aren't guaranteed unique within the log.
+    ///
+    /// 0 and `u64::MAX` are replaced with `EXCLUSIVE`, since the OS ignores
+    /// signposts using them.
+    pub fn from_value(id: u64) -> Self {
+        match id {
+            OS_SIGNPOST_ID_NULL | OS_SIGNPOST_ID_INVALID => Self::EXCLUSIVE,
+            id => Self(id),
+        }
+    }
+
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+/// Signposts show up in Instruments' Points of Interest and os_signpost
+/// instruments. os_signpost needs a literal name, so every event is named
+/// "Event" and every interval "Interval", and `name` is prefixed to the
+/// message instead.
+impl OsLog {
+    /// False when nothing is recording signposts, so callers can skip
+    /// building expensive messages.
+    pub fn signposts_enabled(&self) -> bool {
+        unsafe { os_signpost_enabled(self.inner) }
+    }
+
+    pub fn signpost_event(&self, id: SignpostId, name: &str, message: &str) {
+        self.emit_signpost(name, message, |message| unsafe {
+            wrapped_os_signpost_event_emit(self.inner, id.0, message.as_ptr())
+        });
+    }
+
+    pub fn signpost_interval_begin(&self, id: SignpostId, name: &str, message: &str) {
+        self.emit_signpost(name, message, |message| unsafe {
+            wrapped_os_signpost_interval_begin(self.inner, id.0, message.as_ptr())
+        });
+    }
+
+    pub fn signpost_interval_end(&self, id: SignpostId, name: &str, message: &str) {
+        self.emit_signpost(name, message, |message| unsafe {
+            wrapped_os_signpost_interval_end(self.inner, id.0, message.as_ptr())
+        });
+    }
+
+    /// Emits an interval which began at `start` and ended at `end`, for
+    /// operations timed before anything was logged, e.g. by another library.
+    ///
+    /// `Instant`s are converted to the clock signposts use relative to the
+    /// current time, so the interval may be off by a few microseconds, and
+    /// by more if the machine slept between `start` and now.
+    pub fn signpost_interval_at(
+        &self,
+        id: SignpostId,
+        name: &str,
+        message: &str,
+        start: Instant,
+        end: Instant,
+    ) {
+        let (start, end) = (mach_time(start), mach_time(end));
+        self.emit_signpost(name, message, |message| unsafe {
+            wrapped_os_signpost_interval_at(self.inner, id.0, message.as_ptr(), start, end)
+        });
+    }
+
+    /// Begins an interval with a new ID, which ends when the returned guard
+    /// is dropped.
+    pub fn signpost_interval<'a>(&'a self, name: &'a str) -> SignpostInterval<'a> {
+        self.signpost_interval_with_id(SignpostId::generate(self), name)
+    }
+
+    /// Like `signpost_interval`, with `id` instead of a new one.
+    pub(crate) fn signpost_interval_with_id<'a>(
+        &'a self,
+        id: SignpostId,
+        name: &'a str,
+    ) -> SignpostInterval<'a> {
+        self.signpost_interval_begin(id, name, "");
+        SignpostInterval {
+            log: self,
+            id,
+            name,
+        }
+    }
+
+    pub(crate) fn emit_signpost(&self, name: &str, message: &str, emit: impl Fn(&CStr)) {
+        if cfg!(feature = "off") {
+            return;
+        }
+
+        let message = if message.is_empty() {
+            to_cstr(name, self.nul_bytes)
+        } else {
+            to_cstr(&format!("{}: {}", name, message), self.nul_bytes)
+        };
+
+        if let Ok(message) = message {
+            emit(&message);
+        }
+    }
+}
+
+/// Ends its interval when dropped.
+#[must_use = "the interval ends when the guard is dropped"]
+pub struct SignpostInterval<'a> {
+    log: &'a OsLog,
+    id: SignpostId,
+    name: &'a str,
+}
+
+impl SignpostInterval<'_> {
+    pub fn id(&self) -> SignpostId {
+        self.id
+    }
+}
+
+impl Drop for SignpostInterval<'_> {
+    fn drop(&mut self) {
+        self.log.signpost_interval_end(self.id, self.name, "");
+    }
+}
+
+/// Begins an interval named after the enclosing function, e.g.
+/// "app::db::load", which ends when the returned guard is dropped.
+///
+/// ```no_run
+/// # use oslog::OsLog;
+/// fn load(log: &OsLog) {
+///     let _interval = oslog::signpost_here!(log);
+///     // ...
+/// }
+/// ```
+#[macro_export]
+macro_rules! signpost_here {
+    ($log:expr) => {
+        $log.signpost_interval({
+            fn here() {}
+            $crate::__enclosing_function(here)
+        })
+    };
+}
+
+/// Strips the `here` function `signpost_here!` defines, and any closures,
+/// from its type name.
+#[doc(hidden)]
+pub fn __enclosing_function<F>(_here: F) -> &'static str {
+    let here = std::any::type_name::<F>();
+    let mut name = here.strip_suffix("::here").unwrap_or(here);
+    while let Some(outer) = name.strip_suffix("::{{closure}}") {
+        name = outer;
+    }
+    name
+}
+
+/// Converts `instant` to mach continuous time ticks.
+pub(crate) fn mach_time(instant: Instant) -> u64 {
+    let now = Instant::now();
+    let ticks_now = unsafe { mach_continuous_time() };
+
+    let mut timebase = mach_timebase_info::default();
+    unsafe { mach_timebase_info(&mut timebase) };
+    let to_ticks = |duration: Duration| {
+        (duration.as_nanos() * timebase.denom as u128 / timebase.numer as u128) as u64
+    };
+
+    match now.checked_duration_since(instant) {
+        Some(ago) => ticks_now.saturating_sub(to_ticks(ago)),
+        None => ticks_now + to_ticks(instant - now),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(target_vendor = "apple"))]
+    #[test]
+    fn test_unsupported() {
+        let log = OsLog::new("com.example.oslog", "signposts");
+        assert!(!log.signposts_enabled());
+        assert_eq!(SignpostId::generate(&log), SignpostId::EXCLUSIVE);
+
+        let _interval = log.signpost_interval("Interval");
+        log.signpost_event(SignpostId::EXCLUSIVE, "Event", "");
+    }
+
+    #[cfg(target_vendor = "apple")]
+    #[test]
+    fn test_generate() {
+        let log = OsLog::new("com.example.oslog", "signposts");
+        let first = SignpostId::generate(&log);
+        let second = SignpostId::generate(&log);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_from_value() {
+        assert_eq!(SignpostId::from_value(0), SignpostId::EXCLUSIVE);
+        assert_eq!(SignpostId::from_value(!0), SignpostId::EXCLUSIVE);
+        assert_eq!(SignpostId::from_value(42).as_u64(), 42);
+    }
+
+    #[test]
+    fn test_signposts() {
+        let log = OsLog::new("com.example.oslog", "signposts");
+        let id = SignpostId::generate(&log);
+        log.signpost_interval_begin(id, "Load", "config.toml");
+        log.signpost_event(id, "Parsed", "");
+        log.signpost_interval_end(id, "Load", "");
+        log.signpost_event(SignpostId::EXCLUSIVE, "Nul\0byte", "");
+    }
+
+    #[test]
+    fn test_signpost_here() {
+        let log = OsLog::new("com.example.oslog", "signposts");
+        let interval = signpost_here!(log);
+        assert_eq!(interval.name, "oslog::signpost::tests::test_signpost_here");
+
+        let in_closure = || signpost_here!(log).name.to_string();
+        assert_eq!(in_closure(), "oslog::signpost::tests::test_signpost_here");
+    }
+
+    #[cfg(target_vendor = "apple")]
+    #[test]
+    fn test_mach_time() {
+        let now = Instant::now();
+        let earlier = mach_time(now - Duration::from_millis(10));
+        let later = mach_time(now + Duration::from_millis(10));
+        assert!(earlier < mach_time(now));
+        assert!(mach_time(now) < later);
+    }
+
+    #[test]
+    fn test_interval_at() {
+        let log = OsLog::new("com.example.oslog", "signposts");
+        let end = Instant::now();
+        let start = end - Duration::from_millis(5);
+        log.signpost_interval_at(SignpostId::generate(&log), "Fetch", "", start, end);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{OsLog, SignpostId};
+
+/// Emits signposts to a log like Swift's `OSSignposter`, where beginning an
+/// interval returns an `IntervalState` which ending it consumes, so an
+/// interval can't be ended with a different name or ID, or twice.
+///
+/// Intervals are emitted with the ID they're begun with, so intervals
+/// sharing an ID must nest, as described on `SignpostId`.
+///
+/// ```no_run
+/// # use oslog::{OsLog, Signposter};
+/// let signposter = Signposter::new(OsLog::new("com.example.test", "Sync"));
+/// let state = signposter.begin_interval("Fetch", signposter.make_signpost_id());
+/// // ...
+/// signposter.end_interval(state);
+/// ```
+///
+/// ```compile_fail
+/// # use oslog::{OsLog, Signposter};
+/// # let signposter = Signposter::new(OsLog::new("com.example.test", "Sync"));
+/// let state = signposter.begin_interval("Fetch", signposter.make_signpost_id());
+/// signposter.end_interval(state);
+/// signposter.end_interval(state);
+/// ```
+pub struct Signposter {
+    log: OsLog,
+}
+
+/// An interval begun by `Signposter::begin_interval`, to be passed to
+/// `end_interval` on the same signposter.
+#[derive(Debug)]
+#[must_use = "the interval only ends when passed to `end_interval`"]
+pub struct IntervalState {
+    name: &'static str,
+    id: SignpostId,
+}
+
+impl IntervalState {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn id(&self) -> SignpostId {
+        self.id
+    }
+}
+
+impl Signposter {
+    pub fn new(log: OsLog) -> Self {
+        Self { log }
+    }
+
+    pub fn log(&self) -> &OsLog {
+        &self.log
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.log.signposts_enabled()
+    }
+
+    /// Generates an ID which is unique within the log.
+    pub fn make_signpost_id(&self) -> SignpostId {
+        SignpostId::generate(&self.log)
+    }
+
+    pub fn emit_event(&self, name: &'static str, id: SignpostId, message: &str) {
+        self.log.signpost_event(id, name, message);
+    }
+
+    pub fn begin_interval(&self, name: &'static str, id: SignpostId) -> IntervalState {
+        self.begin_interval_with_message(name, id, "")
+    }
+
+    pub fn begin_interval_with_message(
+        &self,
+        name: &'static str,
+        id: SignpostId,
+        message: &str,
+    ) -> IntervalState {
+        self.log.signpost_interval_begin(id, name, message);
+        IntervalState { name, id }
+    }
+
+    pub fn end_interval(&self, state: IntervalState) {
+        self.end_interval_with_message(state, "");
+    }
+
+    pub fn end_interval_with_message(&self, state: IntervalState, message: &str) {
+        self.log
+            .signpost_interval_end(state.id, state.name, message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signposter() {
+        let signposter = Signposter::new(OsLog::new("com.example.oslog", "signposter"));
+        let id = signposter.make_signpost_id();
+
+        let state = signposter.begin_interval_with_message("Fetch", id, "config.toml");
+        assert_eq!(state.name(), "Fetch");
+        assert_eq!(state.id(), id);
+
+        let nested = signposter.begin_interval("Parse", id);
+        assert_eq!(nested.id(), id);
+
+        signposter.emit_event("Parsed", id, "");
+        signposter.end_interval(nested);
+        signposter.end_interval(state);
+    }
+}
@@ -0,0 +1,182 @@
+//! Switching the whole crate between the unified logging system and an
+//! in-memory ring buffer, for environments such as sandboxed CI runners where
+//! os_log output is restricted or can't be observed.
+//!
+//! Setting `OSLOG_STDERR=1` sends every message to stderr instead, for when
+//! there's no chance to enable the memory sink from code.
+
+use crate::Level;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::os::unix::io::{FromRawFd, RawFd};
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+/// An entry recorded while the memory sink is enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryEntry {
+    pub subsystem: String,
+    pub category: String,
+    pub level: Level,
+    pub message: String,
+}
+
+struct RingBuffer {
+    entries: VecDeque<MemoryEntry>,
+    capacity: usize,
+}
+
+impl RingBuffer {
+    fn push(&mut self, entry: MemoryEntry) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(entry);
+    }
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static BUFFER: Mutex<Option<RingBuffer>> = Mutex::new(None);
+
+/// Sends every message to a ring buffer holding the latest `capacity`
+/// entries instead of the unified logging system. Previously recorded
+/// entries are discarded.
+pub fn enable_memory(capacity: usize) {
+    *BUFFER.lock().unwrap_or_else(|e| e.into_inner()) = Some(RingBuffer {
+        entries: VecDeque::with_capacity(capacity),
+        capacity,
+    });
+    ENABLED.store(true, Ordering::SeqCst);
+}
+
+/// Sends messages to the unified logging system again, discarding the
+/// recorded entries.
+pub fn disable_memory() {
+    ENABLED.store(false, Ordering::SeqCst);
+    *BUFFER.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+pub fn is_memory_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Returns the recorded entries, oldest first.
+pub fn entries() -> Vec<MemoryEntry> {
+    BUFFER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .map(|buffer| buffer.entries.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Removes and returns the recorded entries, oldest first.
+pub fn drain() -> Vec<MemoryEntry> {
+    BUFFER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_mut()
+        .map(|buffer| buffer.entries.drain(..).collect())
+        .unwrap_or_default()
+}
+
+/// The environment variable which sends messages to stderr when set to `1`.
+pub const STDERR_VAR: &str = "OSLOG_STDERR";
+
+static STDERR_ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// Where stderr output goes. `capture_std_streams` points this at a copy of
+/// the original stderr so messages aren't captured and logged again.
+static STDERR_FD: AtomicI32 = AtomicI32::new(libc::STDERR_FILENO);
+
+/// Whether `OSLOG_STDERR=1` was set when the first message was logged.
+pub fn is_stderr_enabled() -> bool {
+    *STDERR_ENABLED.get_or_init(|| std::env::var(STDERR_VAR).is_ok_and(|value| value == "1"))
+}
+
+pub(crate) fn set_stderr_fd(fd: RawFd) {
+    STDERR_FD.store(fd, Ordering::SeqCst);
+}
+
+fn format_entry(entry: &MemoryEntry) -> String {
+    format!(
+        "[{}:{}] {:?}: {}\n",
+        entry.subsystem, entry.category, entry.level, entry.message
+    )
+}
+
+fn write_stderr(entry: &MemoryEntry) {
+    // Borrow the descriptor without closing it.
+    let mut stderr = std::mem::ManuallyDrop::new(unsafe {
+        std::fs::File::from_raw_fd(STDERR_FD.load(Ordering::Relaxed))
+    });
+    let _ = stderr.write_all(format_entry(entry).as_bytes());
+}
+
+/// Returns false if neither the memory sink nor stderr are enabled and
+/// `entry` should go to the unified logging system.
+pub(crate) fn record(entry: impl FnOnce() -> MemoryEntry) -> bool {
+    if is_memory_enabled() {
+        // Might have been disabled since the flag was checked.
+        if let Some(buffer) = BUFFER.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            buffer.push(entry());
+            return true;
+        }
+    }
+
+    if is_stderr_enabled() {
+        write_stderr(&entry());
+        return true;
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(message: &str) -> MemoryEntry {
+        MemoryEntry {
+            subsystem: "com.example.oslog".into(),
+            category: "sink".into(),
+            level: Level::Error,
+            message: message.into(),
+        }
+    }
+
+    #[test]
+    fn test_format_entry() {
+        assert_eq!(
+            format_entry(&entry("Hello")),
+            "[com.example.oslog:sink] Error: Hello\n"
+        );
+    }
+
+    #[test]
+    fn test_ring_buffer() {
+        let mut buffer = RingBuffer {
+            entries: VecDeque::new(),
+            capacity: 2,
+        };
+
+        buffer.push(entry("One"));
+        buffer.push(entry("Two"));
+        buffer.push(entry("Three"));
+
+        assert_eq!(buffer.entries, vec![entry("Two"), entry("Three")]);
+
+        let mut buffer = RingBuffer {
+            entries: VecDeque::new(),
+            capacity: 0,
+        };
+
+        buffer.push(entry("One"));
+        assert!(buffer.entries.is_empty());
+    }
+}
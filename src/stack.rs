@@ -0,0 +1,121 @@
+use crate::{Level, OsLog, Truncation, BACKTRACE_CHUNK_LENGTH};
+use std::backtrace::Backtrace;
+
+/// Removes generic arguments, e.g. "catch_unwind::<i32, F>" becomes
+/// "catch_unwind".
+fn strip_generics(symbol: &str) -> String {
+    let mut stripped = String::with_capacity(symbol.len());
+    let mut depth = 0;
+    let mut rest = symbol;
+
+    while let Some(c) = rest.chars().next() {
+        if depth == 0 && rest.starts_with("::<") {
+            depth = 1;
+            rest = &rest[3..];
+            continue;
+        }
+
+        match c {
+            '<' if depth > 0 => depth += 1,
+            '>' if depth > 0 => depth -= 1,
+            _ if depth == 0 => stripped.push(c),
+            _ => {}
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+
+    stripped
+}
+
+/// Drops the column, and the toolchain's path for standard library files.
+fn shorten_location(location: &str) -> &str {
+    let location = match location.rfind(':') {
+        Some(column) if location[column + 1..].bytes().all(|b| b.is_ascii_digit()) => {
+            &location[..column]
+        }
+        _ => location,
+    };
+
+    match location.find("/library/") {
+        Some(library) if location.starts_with("/rustc/") => &location[library + 1..],
+        _ => location,
+    }
+}
+
+/// Formats a backtrace's frames one per line, as "index: symbol (file:line)",
+/// from its `Display` output.
+fn format_compact(backtrace: &str) -> String {
+    let mut frames: Vec<String> = Vec::new();
+
+    for line in backtrace.lines().map(str::trim) {
+        if let Some(location) = line.strip_prefix("at ") {
+            if let Some(frame) = frames.last_mut() {
+                frame.push_str(&format!(" ({})", shorten_location(location)));
+            }
+        } else if let Some((index, symbol)) = line.split_once(": ") {
+            if index.bytes().all(|b| b.is_ascii_digit()) {
+                frames.push(format!("{}: {}", index, strip_generics(symbol)));
+            }
+        }
+    }
+
+    frames.join("\n")
+}
+
+impl OsLog {
+    /// Logs `message` at the error level followed by the current backtrace,
+    /// one frame per line, split over as many entries as needed. Unlike
+    /// faults' backtraces this doesn't need the `backtrace` feature.
+    ///
+    /// ```no_run
+    /// # use oslog::OsLog;
+    /// let log = OsLog::new("com.example.test", "Sync");
+    /// log.error_with_backtrace("Unexpected response");
+    /// ```
+    #[track_caller]
+    pub fn error_with_backtrace(&self, message: &str) {
+        if !self.level_is_enabled(Level::Error) {
+            return;
+        }
+
+        let backtrace = Backtrace::force_capture().to_string();
+        let message = self.locate(message);
+        let message = format!("{}\n{}", message, format_compact(&backtrace));
+        let parts = Truncation::Chunk
+            .apply(&message, BACKTRACE_CHUNK_LENGTH)
+            .unwrap_or_default();
+
+        for part in parts {
+            let _ = self.try_with_level_unlocated(Level::Error, &part);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_compact() {
+        let backtrace = "   0: app::main
+   1: std::panicking::catch_unwind::<i32, &dyn Fn<(), Output = i32>>
+             at /rustc/0123abcd/library/std/src/panicking.rs:544:19
+   2: app::load::{{closure}}
+             at ./src/load.rs:12:5
+   3: main";
+
+        assert_eq!(
+            format_compact(backtrace),
+            "0: app::main
+1: std::panicking::catch_unwind (library/std/src/panicking.rs:544)
+2: app::load::{{closure}} (./src/load.rs:12)
+3: main"
+        );
+    }
+
+    #[test]
+    fn test_error_with_backtrace() {
+        let log = OsLog::new("com.example.oslog", "stack");
+        log.error_with_backtrace("Hello");
+    }
+}
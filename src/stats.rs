@@ -0,0 +1,166 @@
+//! Counting the messages `OsLogger` loses, per category, so data loss from
+//! the truncation and NUL byte policies is visible, and optionally the
+//! volume each category logs.
+
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The category `OsLogger::log_stats_every` logs summaries to.
+pub const STATS_CATEGORY: &str = "Stats";
+
+/// A category's counters at the time `OsLoggerHandle::stats` was called.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LogStats {
+    /// Messages rejected by the truncation or NUL byte policy.
+    pub dropped: u64,
+    /// Messages cut short by `Truncation::Ellipsis`.
+    pub truncated: u64,
+    /// Messages logged, with `OsLogger::track_volume`.
+    pub messages: u64,
+    /// Bytes logged before truncation, with `OsLogger::track_volume`.
+    pub bytes: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    dropped: AtomicU64,
+    truncated: AtomicU64,
+    messages: AtomicU64,
+    bytes: AtomicU64,
+}
+
+impl Counters {
+    fn snapshot(&self) -> LogStats {
+        LogStats {
+            dropped: self.dropped.load(Ordering::Relaxed),
+            truncated: self.truncated.load(Ordering::Relaxed),
+            messages: self.messages.load(Ordering::Relaxed),
+            bytes: self.bytes.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Unless volume is tracked, categories are only added once something
+/// happens to one of their messages, so logging normally doesn't touch the
+/// map.
+#[derive(Default)]
+pub(crate) struct Stats {
+    categories: DashMap<String, Counters>,
+}
+
+impl Stats {
+    fn counters(&self, category: &str, f: impl FnOnce(&Counters)) {
+        match self.categories.get(category) {
+            Some(counters) => f(&counters),
+            None => f(&self.categories.entry(category.to_string()).or_default()),
+        }
+    }
+
+    pub(crate) fn dropped(&self, category: &str) {
+        self.counters(category, |counters| {
+            counters.dropped.fetch_add(1, Ordering::Relaxed);
+        });
+    }
+
+    pub(crate) fn truncated(&self, category: &str) {
+        self.counters(category, |counters| {
+            counters.truncated.fetch_add(1, Ordering::Relaxed);
+        });
+    }
+
+    pub(crate) fn logged(&self, category: &str, bytes: usize) {
+        self.counters(category, |counters| {
+            counters.messages.fetch_add(1, Ordering::Relaxed);
+            counters.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+        });
+    }
+
+    pub(crate) fn snapshot(&self) -> HashMap<String, LogStats> {
+        self.categories
+            .iter()
+            .map(|pair| (pair.key().clone(), pair.value().snapshot()))
+            .collect()
+    }
+}
+
+/// Formats the categories whose counters changed since `previous`, sorted by
+/// name.
+pub(crate) fn summary(
+    current: &HashMap<String, LogStats>,
+    previous: &HashMap<String, LogStats>,
+) -> Vec<String> {
+    let mut changed: Vec<_> = current
+        .iter()
+        .filter(|(category, stats)| previous.get(*category) != Some(stats))
+        .collect();
+    changed.sort_by(|a, b| a.0.cmp(b.0));
+
+    changed
+        .into_iter()
+        .map(|(category, stats)| {
+            format!(
+                "Stats category={} messages={} bytes={} dropped={} truncated={}",
+                crate::event::format_value(category),
+                stats.messages,
+                stats.bytes,
+                stats.dropped,
+                stats.truncated
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let stats = Stats::default();
+        assert!(stats.snapshot().is_empty());
+
+        stats.dropped("Network");
+        stats.truncated("Network");
+        stats.truncated("Network");
+        stats.dropped("Disk");
+
+        let snapshot = stats.snapshot();
+        assert_eq!(
+            snapshot["Network"],
+            LogStats {
+                dropped: 1,
+                truncated: 2,
+                ..LogStats::default()
+            }
+        );
+        assert_eq!(snapshot["Disk"].dropped, 1);
+
+        stats.logged("Disk", 5);
+        stats.logged("Disk", 7);
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot["Disk"].messages, 2);
+        assert_eq!(snapshot["Disk"].bytes, 12);
+    }
+
+    #[test]
+    fn test_summary() {
+        let stats = Stats::default();
+        stats.dropped("Network");
+        stats.dropped("Disk");
+        let previous = stats.snapshot();
+
+        stats.truncated("Network");
+        assert_eq!(
+            summary(&stats.snapshot(), &previous),
+            vec!["Stats category=Network messages=0 bytes=0 dropped=1 truncated=1"]
+        );
+        assert_eq!(
+            summary(&previous, &HashMap::new()),
+            vec![
+                "Stats category=Disk messages=0 bytes=0 dropped=1 truncated=0",
+                "Stats category=Network messages=0 bytes=0 dropped=1 truncated=0"
+            ]
+        );
+    }
+}
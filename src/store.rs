@@ -0,0 +1,516 @@
+//! Reading entries back from the unified logging system.
+
+use crate::{Error, Level};
+use objc::rc::{autoreleasepool, StrongPtr};
+use objc::runtime::{Class, Object, BOOL, YES};
+use objc::{class, msg_send, sel, sel_impl};
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::ptr;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+mod export;
+mod predicate;
+mod signpost;
+
+pub use export::write_csv;
+#[cfg(feature = "serde")]
+pub use export::write_jsonl;
+pub use predicate::{category, level_at_least, message_contains, subsystem, Predicate};
+pub use signpost::{intervals, SignpostEntry, SignpostInterval, SignpostKind};
+
+#[link(name = "Foundation", kind = "framework")]
+extern "C" {}
+
+#[link(name = "OSLog", kind = "framework")]
+extern "C" {}
+
+/// `OSLogStoreScope.currentProcessIdentifier`
+const OS_LOG_STORE_CURRENT_PROCESS_IDENTIFIER: isize = 1;
+
+/// How often `LogStore::stream` checks for new entries.
+pub const STREAM_INTERVAL: Duration = Duration::from_millis(250);
+
+/// `NSUTF8StringEncoding`
+const NS_UTF8_STRING_ENCODING: usize = 4;
+
+/// Converts to `OSLogEntryLogLevel`.
+fn to_entry_level(level: Level) -> isize {
+    match level {
+        Level::Debug => 1,
+        Level::Info => 2,
+        Level::Default => 3,
+        Level::Error => 4,
+        Level::Fault => 5,
+    }
+}
+
+/// Converts from `OSLogEntryLogLevel`.
+fn from_entry_level(level: isize) -> Level {
+    match level {
+        1 => Level::Debug,
+        2 => Level::Info,
+        4 => Level::Error,
+        5 => Level::Fault,
+        // Notice and undefined.
+        _ => Level::Default,
+    }
+}
+
+unsafe fn to_ns_string(string: &str) -> StrongPtr {
+    let ns_string: *mut Object = msg_send![class!(NSString), alloc];
+    let ns_string: *mut Object = msg_send![
+        ns_string,
+        initWithBytes: string.as_ptr()
+        length: string.len()
+        encoding: NS_UTF8_STRING_ENCODING
+    ];
+
+    StrongPtr::new(ns_string)
+}
+
+unsafe fn to_string(ns_string: *mut Object) -> String {
+    if ns_string.is_null() {
+        return String::new();
+    }
+
+    // UTF8String returns NULL when the string can't be converted.
+    let utf8: *const c_char = msg_send![ns_string, UTF8String];
+    if utf8.is_null() {
+        return String::new();
+    }
+
+    CStr::from_ptr(utf8).to_string_lossy().into_owned()
+}
+
+unsafe fn to_system_time(ns_date: *mut Object) -> SystemTime {
+    let seconds: f64 = msg_send![ns_date, timeIntervalSince1970];
+
+    if seconds >= 0.0 {
+        UNIX_EPOCH + Duration::from_secs_f64(seconds)
+    } else {
+        UNIX_EPOCH - Duration::from_secs_f64(-seconds)
+    }
+}
+
+unsafe fn to_ns_date(time: SystemTime) -> *mut Object {
+    msg_send![class!(NSDate), dateWithTimeIntervalSince1970: export::unix_seconds(time)]
+}
+
+unsafe fn to_error(ns_error: *mut Object) -> Error {
+    if ns_error.is_null() {
+        return Error::Store("unknown error".into());
+    }
+
+    let description: *mut Object = msg_send![ns_error, localizedDescription];
+    Error::Store(to_string(description))
+}
+
+fn class(name: &str) -> Result<&'static Class, Error> {
+    Class::get(name).ok_or_else(|| Error::Store(format!("{} is unavailable", name)))
+}
+
+/// An entry read from a `LogStore`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LogEntry {
+    pub subsystem: String,
+    pub category: String,
+    pub level: Level,
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "export::serialize_timestamp")
+    )]
+    pub timestamp: SystemTime,
+    pub message: String,
+}
+
+impl LogEntry {
+    /// `entry` must be an `OSLogEntryLog`.
+    unsafe fn from_object(entry: *mut Object) -> Self {
+        let level: isize = msg_send![entry, level];
+
+        let subsystem: *mut Object = msg_send![entry, subsystem];
+        let category: *mut Object = msg_send![entry, category];
+        let date: *mut Object = msg_send![entry, date];
+        let message: *mut Object = msg_send![entry, composedMessage];
+
+        Self {
+            subsystem: to_string(subsystem),
+            category: to_string(category),
+            level: from_entry_level(level),
+            timestamp: to_system_time(date),
+            message: to_string(message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Scope {
+    CurrentProcess,
+    Local,
+}
+
+/// A wrapper around `OSLogStore`.
+pub struct LogStore {
+    inner: StrongPtr,
+    scope: Scope,
+}
+
+impl LogStore {
+    /// Opens the entries logged by the current process. Requires macOS 12.
+    pub fn current_process() -> Result<Self, Error> {
+        Self::open(Scope::CurrentProcess)
+    }
+
+    /// Opens the entries logged by the whole system. Requires the process to
+    /// be running as an administrator.
+    pub fn local() -> Result<Self, Error> {
+        Self::open(Scope::Local)
+    }
+
+    fn open(scope: Scope) -> Result<Self, Error> {
+        let store_class = class("OSLogStore")?;
+
+        autoreleasepool(|| unsafe {
+            let mut error: *mut Object = ptr::null_mut();
+            let store: *mut Object = match scope {
+                Scope::CurrentProcess => msg_send![
+                    store_class,
+                    storeWithScope: OS_LOG_STORE_CURRENT_PROCESS_IDENTIFIER
+                    error: &mut error as *mut *mut Object
+                ],
+                Scope::Local => msg_send![
+                    store_class,
+                    localStoreAndReturnError: &mut error as *mut *mut Object
+                ],
+            };
+
+            if store.is_null() {
+                return Err(to_error(error));
+            }
+
+            Ok(Self {
+                inner: StrongPtr::retain(store),
+                scope,
+            })
+        })
+    }
+
+    /// Returns every log entry in the store, oldest first.
+    pub fn entries(&self) -> Result<Entries, Error> {
+        self.enumerate(None, None, None)
+    }
+
+    /// Returns the log entries matching `predicate`, oldest first.
+    pub fn entries_matching(&self, predicate: &Predicate) -> Result<Entries, Error> {
+        self.enumerate(None, None, Some(predicate))
+    }
+
+    /// Returns the log entries logged at or after `since`, oldest first.
+    pub fn entries_since(&self, since: SystemTime) -> Result<Entries, Error> {
+        self.enumerate(Some(since), None, None)
+    }
+
+    /// Returns the log entries logged within the last `duration`, oldest
+    /// first. Durations too long to subtract from the current time return
+    /// everything since the Unix epoch.
+    pub fn entries_within(&self, duration: Duration) -> Result<Entries, Error> {
+        let since = SystemTime::now()
+            .checked_sub(duration)
+            .unwrap_or(UNIX_EPOCH);
+        self.entries_since(since)
+    }
+
+    /// Returns the log entries logged between `start` and `end` inclusive,
+    /// oldest first.
+    pub fn entries_between(&self, start: SystemTime, end: SystemTime) -> Result<Entries, Error> {
+        self.enumerate(Some(start), Some(end), None)
+    }
+
+    /// Returns every signpost in the store, oldest first.
+    pub fn signposts(&self) -> Result<Signposts, Error> {
+        let inner = self.enumerator("OSLogEntrySignpost", None, None, None)?;
+        Ok(Signposts { inner })
+    }
+
+    /// Returns the signposts matching `predicate`, oldest first.
+    pub fn signposts_matching(&self, predicate: &Predicate) -> Result<Signposts, Error> {
+        let inner = self.enumerator("OSLogEntrySignpost", None, None, Some(predicate))?;
+        Ok(Signposts { inner })
+    }
+
+    /// Calls `callback` with each new entry matching `predicate` as it's
+    /// logged, until `callback` returns `false`. The store is polled every
+    /// `STREAM_INTERVAL`, and only entries logged after `stream` is called are
+    /// passed to `callback`.
+    pub fn stream(
+        &self,
+        predicate: &Predicate,
+        mut callback: impl FnMut(LogEntry) -> bool,
+    ) -> Result<(), Error> {
+        let mut since = SystemTime::now();
+        // Entries already passed to `callback` which were logged at `since`.
+        let mut seen = Vec::new();
+
+        loop {
+            // A store is a snapshot, so it has to be reopened to see new
+            // entries.
+            let store = Self::open(self.scope)?;
+
+            for entry in store.enumerate(Some(since), None, Some(predicate))? {
+                if seen.contains(&entry) {
+                    continue;
+                }
+
+                if entry.timestamp > since {
+                    since = entry.timestamp;
+                    seen.clear();
+                }
+
+                seen.push(entry.clone());
+
+                if !callback(entry) {
+                    return Ok(());
+                }
+            }
+
+            thread::sleep(STREAM_INTERVAL);
+        }
+    }
+
+    fn enumerate(
+        &self,
+        since: Option<SystemTime>,
+        until: Option<SystemTime>,
+        predicate: Option<&Predicate>,
+    ) -> Result<Entries, Error> {
+        let inner = self.enumerator("OSLogEntryLog", since, until, predicate)?;
+        Ok(Entries { inner })
+    }
+
+    fn enumerator(
+        &self,
+        class_name: &str,
+        since: Option<SystemTime>,
+        until: Option<SystemTime>,
+        predicate: Option<&Predicate>,
+    ) -> Result<Enumerator, Error> {
+        let entry_class = class(class_name)?;
+        let predicate_class = class("NSPredicate")?;
+
+        autoreleasepool(|| unsafe {
+            let nil: *mut Object = ptr::null_mut();
+
+            let predicate: *mut Object = match predicate {
+                Some(predicate) => {
+                    let format = to_ns_string(predicate.as_str());
+                    msg_send![predicate_class, predicateWithFormat: *format argumentArray: nil]
+                }
+                None => nil,
+            };
+
+            let position: *mut Object = match since {
+                Some(since) => msg_send![*self.inner, positionWithDate: to_ns_date(since)],
+                None => nil,
+            };
+
+            let mut error: *mut Object = ptr::null_mut();
+            let enumerator: *mut Object = msg_send![
+                *self.inner,
+                entriesEnumeratorWithOptions: 0usize
+                position: position
+                predicate: predicate
+                error: &mut error as *mut *mut Object
+            ];
+
+            if enumerator.is_null() {
+                return Err(to_error(error));
+            }
+
+            Ok(Enumerator {
+                inner: StrongPtr::retain(enumerator),
+                class: entry_class,
+                since,
+                until,
+            })
+        })
+    }
+}
+
+/// A wrapper around `OSLogEnumerator`, which only yields entries of one class.
+struct Enumerator {
+    inner: StrongPtr,
+    class: &'static Class,
+    since: Option<SystemTime>,
+    until: Option<SystemTime>,
+}
+
+impl Enumerator {
+    fn next<T>(
+        &mut self,
+        from_object: unsafe fn(*mut Object) -> T,
+        timestamp: fn(&T) -> SystemTime,
+    ) -> Option<T> {
+        loop {
+            let next = autoreleasepool(|| unsafe {
+                let entry: *mut Object = msg_send![*self.inner, nextObject];
+
+                if entry.is_null() {
+                    return None;
+                }
+
+                let is_kind: BOOL = msg_send![entry, isKindOfClass: self.class];
+                if is_kind == YES {
+                    Some(Some(from_object(entry)))
+                } else {
+                    Some(None)
+                }
+            });
+
+            match next {
+                // Positions aren't exact, so entries just before `since` can
+                // be enumerated.
+                Some(Some(entry)) if self.since.is_some_and(|since| timestamp(&entry) < since) => {
+                    continue
+                }
+                Some(Some(entry)) if self.until.is_some_and(|until| timestamp(&entry) > until) => {
+                    return None
+                }
+                Some(Some(entry)) => return Some(entry),
+                Some(None) => continue,
+                None => return None,
+            }
+        }
+    }
+}
+
+/// An iterator over the log messages in a `LogStore`.
+pub struct Entries {
+    inner: Enumerator,
+}
+
+impl Iterator for Entries {
+    type Item = LogEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next(LogEntry::from_object, |entry| entry.timestamp)
+    }
+}
+
+/// An iterator over the signposts in a `LogStore`.
+pub struct Signposts {
+    inner: Enumerator,
+}
+
+impl Iterator for Signposts {
+    type Item = SignpostEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next(SignpostEntry::from_object, |entry| entry.timestamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OsLog;
+
+    #[test]
+    fn test_read_back_current_process() {
+        let log = OsLog::new("com.example.oslog.store", "testing");
+        log.error("Store test");
+
+        let store = LogStore::current_process().unwrap();
+        let entry = store
+            .entries()
+            .unwrap()
+            .find(|entry| entry.subsystem == "com.example.oslog.store")
+            .unwrap();
+
+        assert_eq!(entry.category, "testing");
+        assert_eq!(entry.level, Level::Error);
+        assert_eq!(entry.message, "Store test");
+    }
+
+    #[test]
+    fn test_entries_matching() {
+        let log = OsLog::new("com.example.oslog.store", "matching");
+        log.default("Not matched");
+        log.error("Matched");
+
+        let predicate = subsystem("com.example.oslog.store")
+            .and(category("matching"))
+            .and(level_at_least(Level::Error));
+
+        let store = LogStore::current_process().unwrap();
+        let messages: Vec<_> = store
+            .entries_matching(&predicate)
+            .unwrap()
+            .map(|entry| entry.message)
+            .collect();
+
+        assert_eq!(messages, vec!["Matched"]);
+    }
+
+    #[test]
+    fn test_entries_between() {
+        let log = OsLog::new("com.example.oslog.store", "between");
+        log.error("Before");
+        std::thread::sleep(Duration::from_millis(10));
+
+        let start = SystemTime::now();
+        log.error("During");
+        let end = SystemTime::now();
+
+        std::thread::sleep(Duration::from_millis(10));
+        log.error("After");
+
+        let store = LogStore::current_process().unwrap();
+
+        let messages: Vec<_> = store
+            .entries_between(start, end)
+            .unwrap()
+            .filter(|entry| entry.category == "between")
+            .map(|entry| entry.message)
+            .collect();
+        assert_eq!(messages, vec!["During"]);
+
+        let messages: Vec<_> = store
+            .entries_since(start)
+            .unwrap()
+            .filter(|entry| entry.category == "between")
+            .map(|entry| entry.message)
+            .collect();
+        assert_eq!(messages, vec!["During", "After"]);
+    }
+
+    #[test]
+    fn test_stream() {
+        let store = LogStore::current_process().unwrap();
+        let predicate = subsystem("com.example.oslog.store").and(category("stream"));
+
+        let writer = thread::spawn(|| {
+            let log = OsLog::new("com.example.oslog.store", "stream");
+
+            for i in 0..3 {
+                thread::sleep(STREAM_INTERVAL);
+                log.error(&format!("Stream {}", i));
+            }
+        });
+
+        let mut messages = Vec::new();
+        store
+            .stream(&predicate, |entry| {
+                messages.push(entry.message);
+                messages.len() < 3
+            })
+            .unwrap();
+
+        writer.join().unwrap();
+        assert_eq!(messages, vec!["Stream 0", "Stream 1", "Stream 2"]);
+    }
+}
@@ -0,0 +1,115 @@
+use super::LogEntry;
+use std::borrow::Borrow;
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds since the Unix epoch, negative for earlier times.
+pub(super) fn unix_seconds(time: SystemTime) -> f64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs_f64(),
+        Err(e) => -e.duration().as_secs_f64(),
+    }
+}
+
+#[cfg(feature = "serde")]
+pub(super) fn serialize_timestamp<S: serde::Serializer>(
+    timestamp: &SystemTime,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(unix_seconds(*timestamp))
+}
+
+/// Quotes `field` if it contains a comma, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Writes each entry as a JSON object on its own line. Timestamps are
+/// written as seconds since the Unix epoch.
+#[cfg(feature = "serde")]
+pub fn write_jsonl<W, I>(mut writer: W, entries: I) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator,
+    I::Item: Borrow<LogEntry>,
+{
+    for entry in entries {
+        serde_json::to_writer(&mut writer, entry.borrow())?;
+        writer.write_all(b"\n")?;
+    }
+
+    Ok(())
+}
+
+/// Writes the entries as CSV with a header row. Timestamps are written as
+/// seconds since the Unix epoch.
+pub fn write_csv<W, I>(mut writer: W, entries: I) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator,
+    I::Item: Borrow<LogEntry>,
+{
+    writeln!(writer, "timestamp,level,subsystem,category,message")?;
+
+    for entry in entries {
+        let entry = entry.borrow();
+        writeln!(
+            writer,
+            "{:.6},{},{},{},{}",
+            unix_seconds(entry.timestamp),
+            format!("{:?}", entry.level).to_lowercase(),
+            csv_field(&entry.subsystem),
+            csv_field(&entry.category),
+            csv_field(&entry.message)
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Level;
+    use std::time::Duration;
+
+    fn entry(message: &str) -> LogEntry {
+        LogEntry {
+            subsystem: "com.example.oslog".into(),
+            category: "export".into(),
+            level: Level::Error,
+            timestamp: UNIX_EPOCH + Duration::from_millis(1500),
+            message: message.into(),
+        }
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let mut output = Vec::new();
+        write_csv(&mut output, &[entry("Plain"), entry("a, \"b\"\nc")]).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "timestamp,level,subsystem,category,message\n\
+             1.500000,error,com.example.oslog,export,Plain\n\
+             1.500000,error,com.example.oslog,export,\"a, \"\"b\"\"\nc\"\n"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_write_jsonl() {
+        let mut output = Vec::new();
+        write_jsonl(&mut output, vec![entry("One"), entry("Two\n")]).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"subsystem\":\"com.example.oslog\",\"category\":\"export\",\"level\":\"error\",\"timestamp\":1.5,\"message\":\"One\"}\n\
+             {\"subsystem\":\"com.example.oslog\",\"category\":\"export\",\"level\":\"error\",\"timestamp\":1.5,\"message\":\"Two\\n\"}\n"
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use crate::Level;
+use std::fmt;
+
+/// A filter for log store queries, which compiles to `NSPredicate` format
+/// syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Predicate {
+    format: String,
+}
+
+/// Quotes `value` as an `NSPredicate` string literal.
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl Predicate {
+    /// Matches entries logged to `subsystem`.
+    pub fn subsystem(subsystem: &str) -> Self {
+        Self {
+            format: format!("subsystem == {}", quote(subsystem)),
+        }
+    }
+
+    /// Matches entries logged to `category`.
+    pub fn category(category: &str) -> Self {
+        Self {
+            format: format!("category == {}", quote(category)),
+        }
+    }
+
+    /// Matches entries logged at `level` or above.
+    pub fn level_at_least(level: Level) -> Self {
+        Self {
+            format: format!("level >= {}", super::to_entry_level(level)),
+        }
+    }
+
+    /// Matches entries whose message contains `text`.
+    pub fn message_contains(text: &str) -> Self {
+        Self {
+            format: format!("composedMessage CONTAINS {}", quote(text)),
+        }
+    }
+
+    pub fn and(self, other: Self) -> Self {
+        Self {
+            format: format!("({}) AND ({})", self.format, other.format),
+        }
+    }
+
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            format: format!("({}) OR ({})", self.format, other.format),
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        Self {
+            format: format!("NOT ({})", self.format),
+        }
+    }
+
+    /// Returns the `NSPredicate` format string.
+    pub fn as_str(&self) -> &str {
+        &self.format
+    }
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.format)
+    }
+}
+
+/// Shorthand for `Predicate::subsystem`.
+pub fn subsystem(subsystem: &str) -> Predicate {
+    Predicate::subsystem(subsystem)
+}
+
+/// Shorthand for `Predicate::category`.
+pub fn category(category: &str) -> Predicate {
+    Predicate::category(category)
+}
+
+/// Shorthand for `Predicate::level_at_least`.
+pub fn level_at_least(level: Level) -> Predicate {
+    Predicate::level_at_least(level)
+}
+
+/// Shorthand for `Predicate::message_contains`.
+pub fn message_contains(text: &str) -> Predicate {
+    Predicate::message_contains(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple() {
+        assert_eq!(
+            subsystem("com.example").as_str(),
+            "subsystem == \"com.example\""
+        );
+        assert_eq!(category("Net").as_str(), "category == \"Net\"");
+        assert_eq!(level_at_least(Level::Error).as_str(), "level >= 4");
+        assert_eq!(
+            message_contains("time").as_str(),
+            "composedMessage CONTAINS \"time\""
+        );
+    }
+
+    #[test]
+    fn test_quoting() {
+        assert_eq!(
+            category("a \"b\" \\c").as_str(),
+            "category == \"a \\\"b\\\" \\\\c\""
+        );
+    }
+
+    #[test]
+    fn test_combinators() {
+        let predicate = Predicate::subsystem("com.example")
+            .and(category("Net").or(category("Db")))
+            .and(level_at_least(Level::Error).not());
+
+        assert_eq!(
+            predicate.to_string(),
+            "((subsystem == \"com.example\") AND ((category == \"Net\") OR (category == \"Db\"))) AND (NOT (level >= 4))"
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use super::{to_string, to_system_time};
+use objc::runtime::Object;
+use objc::{msg_send, sel, sel_impl};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+/// Mirrors `OSLogEntrySignpostType`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SignpostKind {
+    Undefined,
+    IntervalBegin,
+    IntervalEnd,
+    Event,
+}
+
+/// The names this crate's C shim gives signposts, since os_signpost needs
+/// literal names. The caller's name starts the message instead.
+const SHIM_NAMES: [&str; 2] = ["Interval", "Event"];
+
+/// Recovers the name and message `OsLog::emit_signpost` combined in to
+/// "name: message", or "name" with an empty message, for signposts named by
+/// the shim. Other signposts are returned as they are.
+fn split_name(name: String, message: String) -> (String, String) {
+    if !SHIM_NAMES.contains(&name.as_str()) {
+        return (name, message);
+    }
+
+    match message.split_once(": ") {
+        Some((name, message)) => (name.to_string(), message.to_string()),
+        None => (message, String::new()),
+    }
+}
+
+/// A signpost read from a `LogStore`. Signposts emitted by this crate have
+/// the name and message they were emitted with, rather than the name the
+/// system records.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SignpostEntry {
+    pub subsystem: String,
+    pub category: String,
+    pub name: String,
+    pub id: u64,
+    pub kind: SignpostKind,
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "super::export::serialize_timestamp")
+    )]
+    pub timestamp: SystemTime,
+    pub message: String,
+}
+
+impl SignpostEntry {
+    /// `entry` must be an `OSLogEntrySignpost`.
+    pub(super) unsafe fn from_object(entry: *mut Object) -> Self {
+        let kind: isize = msg_send![entry, signpostType];
+        let kind = match kind {
+            1 => SignpostKind::IntervalBegin,
+            2 => SignpostKind::IntervalEnd,
+            3 => SignpostKind::Event,
+            _ => SignpostKind::Undefined,
+        };
+
+        let subsystem: *mut Object = msg_send![entry, subsystem];
+        let category: *mut Object = msg_send![entry, category];
+        let name: *mut Object = msg_send![entry, signpostName];
+        let id: u64 = msg_send![entry, signpostIdentifier];
+        let date: *mut Object = msg_send![entry, date];
+        let message: *mut Object = msg_send![entry, composedMessage];
+        let (name, message) = split_name(to_string(name), to_string(message));
+
+        Self {
+            subsystem: to_string(subsystem),
+            category: to_string(category),
+            name,
+            id,
+            kind,
+            timestamp: to_system_time(date),
+            message,
+        }
+    }
+}
+
+/// A begin and end signpost pair.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignpostInterval {
+    pub subsystem: String,
+    pub category: String,
+    pub name: String,
+    pub id: u64,
+    /// The message the interval began with.
+    pub message: String,
+    pub begin: SystemTime,
+    pub end: SystemTime,
+}
+
+impl SignpostInterval {
+    pub fn duration(&self) -> Duration {
+        self.end.duration_since(self.begin).unwrap_or_default()
+    }
+}
+
+/// Pairs interval begin and end signposts with the same subsystem, category,
+/// name and ID, in the order the intervals ended. Nested intervals with the
+/// same ID are paired innermost first, and unpaired signposts are ignored.
+pub fn intervals<I>(signposts: I) -> Vec<SignpostInterval>
+where
+    I: IntoIterator,
+    I::Item: Borrow<SignpostEntry>,
+{
+    let mut open: HashMap<_, Vec<(SystemTime, String)>> = HashMap::new();
+    let mut intervals = Vec::new();
+
+    for signpost in signposts {
+        let signpost = signpost.borrow();
+        let key = (
+            signpost.subsystem.clone(),
+            signpost.category.clone(),
+            signpost.name.clone(),
+            signpost.id,
+        );
+
+        match signpost.kind {
+            SignpostKind::IntervalBegin => open
+                .entry(key)
+                .or_default()
+                .push((signpost.timestamp, signpost.message.clone())),
+            SignpostKind::IntervalEnd => {
+                if let Some((begin, message)) = open.get_mut(&key).and_then(Vec::pop) {
+                    let (subsystem, category, name, id) = key;
+                    intervals.push(SignpostInterval {
+                        subsystem,
+                        category,
+                        name,
+                        id,
+                        message,
+                        begin,
+                        end: signpost.timestamp,
+                    });
+                }
+            }
+            SignpostKind::Event | SignpostKind::Undefined => {}
+        }
+    }
+
+    intervals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    fn signpost(name: &str, id: u64, kind: SignpostKind, millis: u64) -> SignpostEntry {
+        SignpostEntry {
+            subsystem: "com.example.oslog".into(),
+            category: "signposts".into(),
+            name: name.into(),
+            id,
+            kind,
+            timestamp: UNIX_EPOCH + Duration::from_millis(millis),
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_intervals() {
+        let signposts = vec![
+            signpost("load", 1, SignpostKind::IntervalBegin, 0),
+            signpost("load", 2, SignpostKind::IntervalBegin, 5),
+            signpost("load", 1, SignpostKind::Event, 7),
+            signpost("load", 2, SignpostKind::IntervalEnd, 10),
+            signpost("save", 1, SignpostKind::IntervalEnd, 15),
+            signpost("load", 1, SignpostKind::IntervalEnd, 20),
+        ];
+
+        let intervals = intervals(&signposts);
+        assert_eq!(intervals.len(), 2);
+
+        assert_eq!(intervals[0].id, 2);
+        assert_eq!(intervals[0].duration(), Duration::from_millis(5));
+
+        assert_eq!(intervals[1].id, 1);
+        assert_eq!(intervals[1].name, "load");
+        assert_eq!(intervals[1].duration(), Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_split_name() {
+        let split = |name: &str, message: &str| split_name(name.into(), message.into());
+
+        assert_eq!(
+            split("Interval", "load: file=a"),
+            ("load".into(), "file=a".into())
+        );
+        assert_eq!(split("Interval", "load"), ("load".into(), String::new()));
+        assert_eq!(split("Event", "tick: 1: 2"), ("tick".into(), "1: 2".into()));
+        assert_eq!(
+            split("Render", "frame: 1"),
+            ("Render".into(), "frame: 1".into())
+        );
+    }
+
+    #[test]
+    fn test_intervals_from_shim() {
+        // Shaped like what `OsLog::signpost_interval_begin` and `_end` emit.
+        let shim = |message: &str, kind, millis| {
+            let (name, message) = split_name("Interval".into(), message.into());
+            SignpostEntry {
+                name,
+                message,
+                ..signpost("", 7, kind, millis)
+            }
+        };
+        let signposts = vec![
+            shim("load: file=a", SignpostKind::IntervalBegin, 0),
+            shim("save: file=b", SignpostKind::IntervalBegin, 5),
+            shim("save", SignpostKind::IntervalEnd, 10),
+            shim("load", SignpostKind::IntervalEnd, 30),
+        ];
+
+        let intervals: Vec<_> = intervals(signposts)
+            .into_iter()
+            .map(|interval| {
+                let duration = interval.duration();
+                (interval.name, interval.message, duration)
+            })
+            .collect();
+
+        assert_eq!(
+            intervals,
+            vec![
+                ("save".into(), "file=b".into(), Duration::from_millis(5)),
+                ("load".into(), "file=a".into(), Duration::from_millis(30)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_intervals() {
+        let signposts = vec![
+            signpost("load", 1, SignpostKind::IntervalBegin, 0),
+            signpost("load", 1, SignpostKind::IntervalBegin, 5),
+            signpost("load", 1, SignpostKind::IntervalEnd, 10),
+            signpost("load", 1, SignpostKind::IntervalEnd, 30),
+        ];
+
+        let durations: Vec<_> = intervals(signposts)
+            .iter()
+            .map(SignpostInterval::duration)
+            .collect();
+
+        assert_eq!(
+            durations,
+            vec![Duration::from_millis(5), Duration::from_millis(30)]
+        );
+    }
+}
@@ -1,7 +1,10 @@
 #![allow(non_camel_case_types)]
 #![allow(dead_code)]
 
-use std::{ffi::c_void, os::raw::c_char};
+use std::{
+    ffi::c_void,
+    os::raw::{c_char, c_int},
+};
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -10,7 +13,29 @@ pub struct os_log_s {
 }
 
 pub type os_log_t = *mut os_log_s;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct os_activity_s {
+    _unused: [u8; 0],
+}
+
+pub type os_activity_t = *mut os_activity_s;
+
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct os_activity_scope_state_s {
+    pub opaque: [u64; 2],
+}
 pub type os_log_type_t = u8;
+pub type os_signpost_id_t = u64;
+
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct mach_timebase_info {
+    pub numer: u32,
+    pub denom: u32,
+}
 
 pub const OS_LOG_TYPE_DEFAULT: os_log_type_t = 0;
 pub const OS_LOG_TYPE_INFO: os_log_type_t = 1;
@@ -18,22 +43,492 @@ pub const OS_LOG_TYPE_DEBUG: os_log_type_t = 2;
 pub const OS_LOG_TYPE_ERROR: os_log_type_t = 16;
 pub const OS_LOG_TYPE_FAULT: os_log_type_t = 17;
 
-/// Provided by the OS.
+pub const OS_SIGNPOST_ID_NULL: os_signpost_id_t = 0;
+pub const OS_SIGNPOST_ID_INVALID: os_signpost_id_t = !0;
+pub const OS_SIGNPOST_ID_EXCLUSIVE: os_signpost_id_t = 0xEEEEB0B5B2B2EEEE;
+
+// Provided by the OS.
+#[cfg(target_vendor = "apple")]
 extern "C" {
     pub fn os_log_create(subsystem: *const c_char, category: *const c_char) -> os_log_t;
+    pub fn os_retain(object: *mut c_void) -> *mut c_void;
     pub fn os_release(object: *mut c_void);
-    pub fn os_log_type_enabled(log: os_log_t, level: os_log_type_t) -> bool;
+    pub fn pthread_threadid_np(thread: *mut c_void, thread_id: *mut u64) -> c_int;
 }
 
-/// Wrappers defined in wrapper.c because most of the os_log_* APIs are macros.
+// Wrappers defined in wrapper.c because most of the os_log_* APIs are macros.
+#[cfg(target_vendor = "apple")]
 extern "C" {
     pub fn wrapped_get_default_log() -> os_log_t;
+    pub fn wrapped_get_disabled_log() -> os_log_t;
+}
+
+// Everything which logs, or checks whether it would, is replaced with an
+// empty function with the `off` feature, and on other targets.
+#[cfg(all(target_vendor = "apple", not(feature = "off")))]
+extern "C" {
+    pub fn os_log_type_enabled(log: os_log_t, level: os_log_type_t) -> bool;
     pub fn wrapped_os_log_with_type(log: os_log_t, log_type: os_log_type_t, message: *const c_char);
+    pub fn wrapped_os_log_with_type_private(
+        log: os_log_t,
+        log_type: os_log_type_t,
+        message: *const c_char,
+    );
+    pub fn wrapped_os_log_with_dso(
+        dso: *const c_void,
+        format: *const c_char,
+        log: os_log_t,
+        log_type: os_log_type_t,
+        message: *const c_char,
+    );
+    pub fn wrapped_os_log_with_dso_private(
+        dso: *const c_void,
+        format: *const c_char,
+        log: os_log_t,
+        log_type: os_log_type_t,
+        message: *const c_char,
+    );
+    pub fn wrapped_os_log_with_location_dso(
+        dso: *const c_void,
+        format: *const c_char,
+        log: os_log_t,
+        log_type: os_log_type_t,
+        message: *const c_char,
+        module: *const c_char,
+        file: *const c_char,
+        line: u32,
+    );
+    pub fn wrapped_os_log_with_duration_dso(
+        dso: *const c_void,
+        format: *const c_char,
+        log: os_log_t,
+        log_type: os_log_type_t,
+        message: *const c_char,
+        milliseconds: f64,
+    );
     pub fn wrapped_os_log_debug(log: os_log_t, message: *const c_char);
     pub fn wrapped_os_log_info(log: os_log_t, message: *const c_char);
     pub fn wrapped_os_log_default(log: os_log_t, message: *const c_char);
     pub fn wrapped_os_log_error(log: os_log_t, message: *const c_char);
     pub fn wrapped_os_log_fault(log: os_log_t, message: *const c_char);
+    pub fn wrapped_os_log_with_location(
+        log: os_log_t,
+        log_type: os_log_type_t,
+        message: *const c_char,
+        module: *const c_char,
+        file: *const c_char,
+        line: u32,
+    );
+    pub fn wrapped_os_log_with_duration(
+        log: os_log_t,
+        log_type: os_log_type_t,
+        message: *const c_char,
+        milliseconds: f64,
+    );
+}
+
+// Signposts are also replaced on other targets, so instrumented code
+// compiles everywhere.
+#[cfg(all(target_vendor = "apple", not(feature = "off")))]
+extern "C" {
+    pub fn os_signpost_enabled(log: os_log_t) -> bool;
+    pub fn wrapped_os_signpost_event_emit(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        message: *const c_char,
+    );
+    pub fn wrapped_os_signpost_interval_begin(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        message: *const c_char,
+    );
+    pub fn wrapped_os_signpost_interval_end(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        message: *const c_char,
+    );
+    pub fn wrapped_os_signpost_counter(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        key: *const c_char,
+        value: f64,
+    );
+    pub fn wrapped_os_signpost_gauge(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        key: *const c_char,
+        value: f64,
+    );
+    pub fn wrapped_os_signpost_histogram(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        key: *const c_char,
+        value: f64,
+    );
+    pub fn wrapped_os_signpost_interval_at(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        message: *const c_char,
+        begin: u64,
+        end: u64,
+    );
+    pub fn wrapped_os_signpost_allocation(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        size: usize,
+        align: usize,
+    );
+    pub fn wrapped_os_signpost_lock_wait(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        message: *const c_char,
+        begin: u64,
+        end: u64,
+        wait_ns: u64,
+    );
+    pub fn wrapped_os_signpost_io(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        message: *const c_char,
+        begin: u64,
+        end: u64,
+        bytes: u64,
+    );
+}
+
+#[cfg(target_vendor = "apple")]
+extern "C" {
+    pub fn os_signpost_id_generate(log: os_log_t) -> os_signpost_id_t;
+    pub fn mach_timebase_info(info: *mut mach_timebase_info) -> c_int;
+    pub fn mach_continuous_time() -> u64;
+    pub fn os_activity_scope_enter(activity: os_activity_t, state: *mut os_activity_scope_state_s);
+    pub fn os_activity_scope_leave(state: *mut os_activity_scope_state_s);
+    pub fn os_activity_label_useraction(name: *const c_char);
+}
+
+#[cfg(target_vendor = "apple")]
+extern "C" {
+    pub fn wrapped_os_activity_create_trace() -> os_activity_t;
+    pub fn wrapped_os_activity_create_request() -> os_activity_t;
+}
+
+#[cfg(any(feature = "off", not(target_vendor = "apple")))]
+pub use no_log::*;
+
+#[cfg(any(feature = "off", not(target_vendor = "apple")))]
+mod no_log {
+    #![allow(unused_variables, clippy::missing_safety_doc)]
+
+    use super::*;
+
+    #[inline(always)]
+    pub unsafe extern "C" fn os_log_type_enabled(log: os_log_t, level: os_log_type_t) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_log_with_type(
+        log: os_log_t,
+        log_type: os_log_type_t,
+        message: *const c_char,
+    ) {
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_log_with_type_private(
+        log: os_log_t,
+        log_type: os_log_type_t,
+        message: *const c_char,
+    ) {
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_log_with_dso(
+        dso: *const c_void,
+        format: *const c_char,
+        log: os_log_t,
+        log_type: os_log_type_t,
+        message: *const c_char,
+    ) {
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_log_with_dso_private(
+        dso: *const c_void,
+        format: *const c_char,
+        log: os_log_t,
+        log_type: os_log_type_t,
+        message: *const c_char,
+    ) {
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_log_with_location_dso(
+        dso: *const c_void,
+        format: *const c_char,
+        log: os_log_t,
+        log_type: os_log_type_t,
+        message: *const c_char,
+        module: *const c_char,
+        file: *const c_char,
+        line: u32,
+    ) {
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_log_with_duration_dso(
+        dso: *const c_void,
+        format: *const c_char,
+        log: os_log_t,
+        log_type: os_log_type_t,
+        message: *const c_char,
+        milliseconds: f64,
+    ) {
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_log_debug(log: os_log_t, message: *const c_char) {}
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_log_info(log: os_log_t, message: *const c_char) {}
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_log_default(log: os_log_t, message: *const c_char) {}
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_log_error(log: os_log_t, message: *const c_char) {}
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_log_fault(log: os_log_t, message: *const c_char) {}
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_log_with_location(
+        log: os_log_t,
+        log_type: os_log_type_t,
+        message: *const c_char,
+        module: *const c_char,
+        file: *const c_char,
+        line: u32,
+    ) {
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_log_with_duration(
+        log: os_log_t,
+        log_type: os_log_type_t,
+        message: *const c_char,
+        milliseconds: f64,
+    ) {
+    }
+}
+
+#[cfg(any(feature = "off", not(target_vendor = "apple")))]
+pub use no_signposts::*;
+
+#[cfg(any(feature = "off", not(target_vendor = "apple")))]
+mod no_signposts {
+    #![allow(unused_variables, clippy::missing_safety_doc)]
+
+    use super::*;
+
+    #[inline(always)]
+    pub unsafe extern "C" fn os_signpost_enabled(log: os_log_t) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_signpost_event_emit(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        message: *const c_char,
+    ) {
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_signpost_interval_begin(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        message: *const c_char,
+    ) {
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_signpost_interval_end(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        message: *const c_char,
+    ) {
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_signpost_counter(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        key: *const c_char,
+        value: f64,
+    ) {
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_signpost_gauge(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        key: *const c_char,
+        value: f64,
+    ) {
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_signpost_histogram(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        key: *const c_char,
+        value: f64,
+    ) {
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_signpost_interval_at(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        message: *const c_char,
+        begin: u64,
+        end: u64,
+    ) {
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_signpost_allocation(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        size: usize,
+        align: usize,
+    ) {
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_signpost_lock_wait(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        message: *const c_char,
+        begin: u64,
+        end: u64,
+        wait_ns: u64,
+    ) {
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_signpost_io(
+        log: os_log_t,
+        id: os_signpost_id_t,
+        message: *const c_char,
+        begin: u64,
+        end: u64,
+        bytes: u64,
+    ) {
+    }
+}
+
+#[cfg(not(target_vendor = "apple"))]
+pub use unsupported::*;
+
+/// Logs, signpost IDs and activities have no effect on other targets, so
+/// binaries link without wrapper.c. Logs are distinct placeholder pointers,
+/// which are never dereferenced.
+#[cfg(not(target_vendor = "apple"))]
+mod unsupported {
+    #![allow(unused_variables, clippy::missing_safety_doc)]
+
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static CREATED_LOG: u8 = 0;
+    static DEFAULT_LOG: u8 = 0;
+    static DISABLED_LOG: u8 = 0;
+
+    fn placeholder(log: &'static u8) -> os_log_t {
+        log as *const u8 as os_log_t
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn os_log_create(
+        subsystem: *const c_char,
+        category: *const c_char,
+    ) -> os_log_t {
+        placeholder(&CREATED_LOG)
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn os_retain(object: *mut c_void) -> *mut c_void {
+        object
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn os_release(object: *mut c_void) {}
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_get_default_log() -> os_log_t {
+        placeholder(&DEFAULT_LOG)
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_get_disabled_log() -> os_log_t {
+        placeholder(&DISABLED_LOG)
+    }
+
+    /// Numbers threads in the order they first ask.
+    pub unsafe extern "C" fn pthread_threadid_np(
+        thread: *mut c_void,
+        thread_id: *mut u64,
+    ) -> c_int {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        thread_local! {
+            static ID: u64 = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        }
+
+        *thread_id = ID.with(|id| *id);
+        0
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn os_signpost_id_generate(log: os_log_t) -> os_signpost_id_t {
+        OS_SIGNPOST_ID_EXCLUSIVE
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn mach_timebase_info(info: *mut mach_timebase_info) -> c_int {
+        (*info).numer = 1;
+        (*info).denom = 1;
+        0
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn mach_continuous_time() -> u64 {
+        0
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn os_activity_scope_enter(
+        activity: os_activity_t,
+        state: *mut os_activity_scope_state_s,
+    ) {
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn os_activity_scope_leave(state: *mut os_activity_scope_state_s) {}
+
+    #[inline(always)]
+    pub unsafe extern "C" fn os_activity_label_useraction(name: *const c_char) {}
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_activity_create_trace() -> os_activity_t {
+        std::ptr::null_mut()
+    }
+
+    #[inline(always)]
+    pub unsafe extern "C" fn wrapped_os_activity_create_request() -> os_activity_t {
+        std::ptr::null_mut()
+    }
 }
 
 #[cfg(test)]
@@ -89,6 +584,28 @@ mod tests {
                 OS_LOG_TYPE_FAULT,
                 message.as_ptr(),
             );
+            wrapped_os_log_with_type_private(
+                wrapped_get_default_log(),
+                OS_LOG_TYPE_DEBUG,
+                message.as_ptr(),
+            );
+
+            let module = CString::new("oslog::sys::tests").unwrap();
+            let file = CString::new("src/sys.rs").unwrap();
+            wrapped_os_log_with_location(
+                wrapped_get_default_log(),
+                OS_LOG_TYPE_DEBUG,
+                message.as_ptr(),
+                module.as_ptr(),
+                file.as_ptr(),
+                1,
+            );
+            wrapped_os_log_with_duration(
+                wrapped_get_default_log(),
+                OS_LOG_TYPE_DEBUG,
+                message.as_ptr(),
+                1.5,
+            );
         }
     }
 
@@ -115,4 +632,34 @@ mod tests {
             os_release(log as *mut _);
         }
     }
+
+    #[cfg(target_vendor = "apple")]
+    #[test]
+    fn test_signposts() {
+        let subsystem = CString::new("com.example.test").unwrap();
+        let category = CString::new("category").unwrap();
+        let log = unsafe { os_log_create(subsystem.as_ptr(), category.as_ptr()) };
+        let message = CString::new("Hello!").unwrap();
+
+        unsafe {
+            let id = os_signpost_id_generate(log);
+            assert_ne!(id, OS_SIGNPOST_ID_NULL);
+            assert_ne!(id, OS_SIGNPOST_ID_INVALID);
+
+            wrapped_os_signpost_event_emit(log, id, message.as_ptr());
+            wrapped_os_signpost_interval_begin(log, id, message.as_ptr());
+            wrapped_os_signpost_interval_end(log, id, message.as_ptr());
+            wrapped_os_signpost_counter(log, id, message.as_ptr(), 1.0);
+            wrapped_os_signpost_gauge(log, id, message.as_ptr(), 1.0);
+            wrapped_os_signpost_histogram(log, id, message.as_ptr(), 1.0);
+            wrapped_os_signpost_allocation(log, id, 1024, 8);
+
+            let now = mach_continuous_time();
+            wrapped_os_signpost_interval_at(log, id, message.as_ptr(), now - 1, now);
+            wrapped_os_signpost_lock_wait(log, id, message.as_ptr(), now - 1, now, 1000);
+            wrapped_os_signpost_io(log, id, message.as_ptr(), now - 1, now, 4096);
+
+            os_release(log as *mut _);
+        }
+    }
 }
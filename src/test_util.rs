@@ -0,0 +1,113 @@
+//! Swapping the installed logger between tests.
+//!
+//! `log` only lets a logger be installed once per process, so `swap`
+//! installs one which forwards to whichever `OsLogger` is swapped in.
+//! Swapped loggers are leaked, and don't log session markers or periodic
+//! statistics.
+
+use crate::{OsLogger, OsLoggerHandle};
+use log::{LevelFilter, Log, Metadata, Record};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard, RwLock};
+
+static CURRENT: RwLock<Option<&'static OsLogger>> = RwLock::new(None);
+
+/// Held for as long as a logger is swapped in, so tests running in parallel
+/// take turns.
+static SWAP: Mutex<()> = Mutex::new(());
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+struct Forwarder;
+
+static FORWARDER: Forwarder = Forwarder;
+
+fn current() -> Option<&'static OsLogger> {
+    *CURRENT.read().unwrap_or_else(|e| e.into_inner())
+}
+
+impl Log for Forwarder {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        current().is_some_and(|logger| logger.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record) {
+        if let Some(logger) = current() {
+            logger.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(logger) = current() {
+            logger.flush();
+        }
+    }
+}
+
+/// Swaps in the logger `configure` returns, blocking while another test has
+/// one swapped in. It's swapped out again, and the level filter turned off,
+/// when the returned guard is dropped.
+///
+/// The logger is configured once it's this test's turn, since setting its
+/// level filter changes the global one.
+///
+/// Fails if a logger was installed other than by `swap`, e.g. by
+/// `OsLogger::init`.
+///
+/// ```
+/// use log::LevelFilter;
+/// use oslog::OsLogger;
+///
+/// let swapped = oslog::test_util::swap(|| {
+///     OsLogger::new("com.example.test").level_filter(LevelFilter::Info)
+/// })
+/// .unwrap();
+/// log::info!("Logged by the first logger");
+/// swapped.set_level_filter(LevelFilter::Warn);
+/// drop(swapped);
+///
+/// let _swapped = oslog::test_util::swap(|| {
+///     OsLogger::new("com.example.other").level_filter(LevelFilter::Debug)
+/// })
+/// .unwrap();
+/// assert_eq!(log::max_level(), LevelFilter::Debug);
+/// ```
+pub fn swap(configure: impl FnOnce() -> OsLogger) -> Result<Swapped, log::SetLoggerError> {
+    let turn = SWAP.lock().unwrap_or_else(|e| e.into_inner());
+
+    if !INSTALLED.load(Ordering::Relaxed) {
+        log::set_logger(&FORWARDER)?;
+        INSTALLED.store(true, Ordering::Relaxed);
+    }
+
+    let handle = configure().into_handle();
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = Some(handle.logger());
+
+    Ok(Swapped {
+        handle,
+        _turn: turn,
+    })
+}
+
+/// Swaps its logger out when dropped. Derefs to the logger's handle.
+#[must_use = "the logger is swapped out when the guard is dropped"]
+pub struct Swapped {
+    handle: OsLoggerHandle,
+    _turn: MutexGuard<'static, ()>,
+}
+
+impl Deref for Swapped {
+    type Target = OsLoggerHandle;
+
+    fn deref(&self) -> &OsLoggerHandle {
+        &self.handle
+    }
+}
+
+impl Drop for Swapped {
+    fn drop(&mut self) {
+        *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = None;
+        log::set_max_level(LevelFilter::Off);
+    }
+}
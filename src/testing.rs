@@ -0,0 +1,195 @@
+//! Assertions on what the current process has logged, for use in tests.
+//!
+//! Entries are read back from the log store, so debug and info entries are
+//! only visible if the subsystem is configured to persist them, e.g. with
+//! `sudo log config --subsystem com.example --mode persist:debug`.
+
+use crate::store::{LogEntry, LogStore};
+use crate::Level;
+use std::fmt;
+use std::time::SystemTime;
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    Contains(String),
+    Equals(String),
+    Anything,
+}
+
+/// Matches against the messages of captured entries.
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    pattern: Pattern,
+}
+
+impl Matcher {
+    pub fn matches(&self, message: &str) -> bool {
+        match &self.pattern {
+            Pattern::Contains(text) => message.contains(text.as_str()),
+            Pattern::Equals(text) => message == text,
+            Pattern::Anything => true,
+        }
+    }
+}
+
+impl fmt::Display for Matcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.pattern {
+            Pattern::Contains(text) => write!(f, "containing {:?}", text),
+            Pattern::Equals(text) => write!(f, "equal to {:?}", text),
+            Pattern::Anything => write!(f, "with any message"),
+        }
+    }
+}
+
+/// Matches messages which contain `text`.
+pub fn contains(text: &str) -> Matcher {
+    Matcher {
+        pattern: Pattern::Contains(text.into()),
+    }
+}
+
+/// Matches messages which are exactly `text`.
+pub fn equals(text: &str) -> Matcher {
+    Matcher {
+        pattern: Pattern::Equals(text.into()),
+    }
+}
+
+/// Matches every message.
+pub fn anything() -> Matcher {
+    Matcher {
+        pattern: Pattern::Anything,
+    }
+}
+
+/// Captures the entries logged by the current process after it was started.
+pub struct Capture {
+    since: SystemTime,
+    subsystem: Option<String>,
+}
+
+impl Capture {
+    pub fn start() -> Self {
+        Self {
+            since: SystemTime::now(),
+            subsystem: None,
+        }
+    }
+
+    /// Only capture entries logged to `subsystem`.
+    pub fn subsystem(mut self, subsystem: &str) -> Self {
+        self.subsystem = Some(subsystem.into());
+        self
+    }
+
+    /// Returns the captured entries, oldest first.
+    pub fn entries(&self) -> Vec<LogEntry> {
+        let store = LogStore::current_process().expect("Couldn't open the log store");
+
+        store
+            .entries_since(self.since)
+            .expect("Couldn't read the log store")
+            .filter(|entry| {
+                self.subsystem
+                    .as_ref()
+                    .map_or(true, |subsystem| entry.subsystem == *subsystem)
+            })
+            .collect()
+    }
+
+    /// Panics unless an entry at `level` matching `matcher` was captured.
+    #[track_caller]
+    pub fn assert_logged(&self, level: Level, matcher: Matcher) {
+        let entries = self.entries();
+
+        if !find(&entries, level, &matcher) {
+            panic!(
+                "Expected a {:?} entry {}, captured:\n{}",
+                level,
+                matcher,
+                describe(&entries)
+            );
+        }
+    }
+
+    /// Panics if an entry at `level` matching `matcher` was captured.
+    #[track_caller]
+    pub fn assert_not_logged(&self, level: Level, matcher: Matcher) {
+        let entries = self.entries();
+
+        if find(&entries, level, &matcher) {
+            panic!(
+                "Expected no {:?} entry {}, captured:\n{}",
+                level,
+                matcher,
+                describe(&entries)
+            );
+        }
+    }
+}
+
+fn find(entries: &[LogEntry], level: Level, matcher: &Matcher) -> bool {
+    entries
+        .iter()
+        .any(|entry| entry.level == level && matcher.matches(&entry.message))
+}
+
+fn describe(entries: &[LogEntry]) -> String {
+    entries
+        .iter()
+        .map(|entry| {
+            format!(
+                "  [{:?}] {}:{} {}",
+                entry.level, entry.subsystem, entry.category, entry.message
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Panics unless the current process has logged an entry at `level`
+/// matching `matcher`.
+#[track_caller]
+pub fn assert_logged(level: Level, matcher: Matcher) {
+    let capture = Capture {
+        since: SystemTime::UNIX_EPOCH,
+        subsystem: None,
+    };
+
+    capture.assert_logged(level, matcher);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OsLog;
+
+    #[test]
+    fn test_matchers() {
+        assert!(contains("time").matches("timeout"));
+        assert!(!contains("time").matches("deadline"));
+        assert!(equals("timeout").matches("timeout"));
+        assert!(!equals("time").matches("timeout"));
+        assert!(anything().matches(""));
+    }
+
+    #[test]
+    fn test_capture() {
+        let capture = Capture::start().subsystem("com.example.oslog.testing");
+
+        let log = OsLog::new("com.example.oslog.testing", "capture");
+        log.error("Request timeout");
+
+        capture.assert_logged(Level::Error, contains("timeout"));
+        capture.assert_not_logged(Level::Fault, anything());
+        assert_logged(Level::Error, equals("Request timeout"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected a Fault entry")]
+    fn test_capture_failure() {
+        let capture = Capture::start().subsystem("com.example.oslog.testing");
+        capture.assert_logged(Level::Fault, anything());
+    }
+}
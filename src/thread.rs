@@ -0,0 +1,165 @@
+//! Spawning threads which log when they start and stop, for finding
+//! problems caused by creating too many of them.
+
+use crate::sys::*;
+use crate::{Error, Level, OsLog};
+use std::io;
+use std::sync::OnceLock;
+use std::thread::{self, JoinHandle};
+
+/// The category thread lifecycle messages are logged to.
+pub const CATEGORY: &str = "Threads";
+
+static LOG: OnceLock<OsLog> = OnceLock::new();
+
+/// Sets the subsystem thread lifecycle messages are logged to. Until it's
+/// called they go to the default log.
+pub fn install(subsystem: &str) -> Result<(), Error> {
+    LOG.set(OsLog::new(subsystem, CATEGORY))
+        .map_err(|_| Error::AlreadyInstalled)
+}
+
+fn log() -> &'static OsLog {
+    static GLOBAL: OnceLock<OsLog> = OnceLock::new();
+    LOG.get()
+        .unwrap_or_else(|| GLOBAL.get_or_init(OsLog::global))
+}
+
+/// The system's ID for the current thread, as shown in Console.
+fn thread_id() -> u64 {
+    let mut id = 0;
+    unsafe { pthread_threadid_np(std::ptr::null_mut(), &mut id) };
+    id
+}
+
+/// Logs that the thread stopped when dropped, including when it panics.
+struct Lifecycle<'a> {
+    log: &'a OsLog,
+    name: &'a str,
+    id: u64,
+}
+
+impl<'a> Lifecycle<'a> {
+    fn start(log: &'a OsLog, name: &'a str) -> Self {
+        let id = thread_id();
+        log.with_level(
+            Level::Info,
+            format_args!("Thread started name={} tid={}", name, id),
+        );
+        Self { log, name, id }
+    }
+}
+
+impl Drop for Lifecycle<'_> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            self.log.with_level(
+                Level::Error,
+                format_args!("Thread panicked name={} tid={}", self.name, self.id),
+            );
+        } else {
+            self.log.with_level(
+                Level::Info,
+                format_args!("Thread stopped name={} tid={}", self.name, self.id),
+            );
+        }
+    }
+}
+
+/// Spawns named threads which log when they start and stop, with their
+/// thread ID.
+///
+/// ```no_run
+/// let handle = oslog::thread::Builder::new("worker")
+///     .signpost_interval(true)
+///     .spawn(|| 1 + 1)
+///     .unwrap();
+///
+/// assert_eq!(handle.join().unwrap(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Builder {
+    name: String,
+    signpost_interval: bool,
+}
+
+impl Builder {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            signpost_interval: false,
+        }
+    }
+
+    /// Wraps the thread's body in a signpost interval named after the
+    /// thread, so its lifetime shows up in Instruments.
+    pub fn signpost_interval(mut self, enabled: bool) -> Self {
+        self.signpost_interval = enabled;
+        self
+    }
+
+    pub fn spawn<F, T>(self, f: F) -> io::Result<JoinHandle<T>>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let name = self.name.clone();
+        let signpost_interval = self.signpost_interval;
+
+        thread::Builder::new().name(self.name).spawn(move || {
+            let log = log();
+            let _lifecycle = Lifecycle::start(log, &name);
+            let _interval = if signpost_interval {
+                Some(log.signpost_interval(&name))
+            } else {
+                None
+            };
+
+            f()
+        })
+    }
+}
+
+/// Spawns a thread named `name` which logs when it starts and stops. Use
+/// `Builder` to also wrap it in a signpost interval.
+///
+/// ```no_run
+/// let handle = oslog::thread::spawn_named("worker", || println!("Working")).unwrap();
+/// handle.join().unwrap();
+/// ```
+pub fn spawn_named<F, T>(name: &str, f: F) -> io::Result<JoinHandle<T>>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    Builder::new(name).spawn(f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spawn_named() {
+        let handle = spawn_named("oslog-test", || thread::current().name().map(String::from));
+        assert_eq!(
+            handle.unwrap().join().unwrap().as_deref(),
+            Some("oslog-test")
+        );
+    }
+
+    #[test]
+    fn test_signpost_interval() {
+        let handle = Builder::new("oslog-test")
+            .signpost_interval(true)
+            .spawn(thread_id)
+            .unwrap();
+        assert_ne!(handle.join().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_panic() {
+        let handle = spawn_named("oslog-test", || panic!("Expected")).unwrap();
+        assert!(handle.join().is_err());
+    }
+}
@@ -0,0 +1,131 @@
+use crate::sys::*;
+use crate::{Level, OsLog, OsLogLike};
+use std::time::{Duration, Instant};
+
+/// Formats `duration` in seconds, milliseconds or microseconds, whichever is
+/// the largest unit it's at least one of, with two decimal places.
+pub(crate) fn format_duration(duration: Duration) -> String {
+    if duration >= Duration::from_secs(1) {
+        format!("{:.2}s", duration.as_secs_f64())
+    } else if duration >= Duration::from_millis(1) {
+        format!("{:.2}ms", duration.as_secs_f64() * 1e3)
+    } else {
+        format!("{:.2}µs", duration.as_secs_f64() * 1e6)
+    }
+}
+
+fn with_elapsed(message: &str, start: Instant) -> String {
+    format!("{} (took {})", message, format_duration(start.elapsed()))
+}
+
+#[track_caller]
+fn scope<T>(log: &impl OsLogLike, name: &str, f: impl FnOnce() -> T) -> T {
+    log.default(&format!("{}: begin", name));
+    let start = Instant::now();
+    let result = f();
+    log.default(&format!(
+        "{}: end (took {})",
+        name,
+        format_duration(start.elapsed())
+    ));
+    result
+}
+
+impl OsLog {
+    /// Runs `f` between default level "`name`: begin" and "`name`: end (took
+    /// 1.23ms)" messages, for timing operations in Console without running
+    /// Instruments.
+    #[track_caller]
+    pub fn scope<T>(&self, name: &str, f: impl FnOnce() -> T) -> T {
+        scope(self, name, f)
+    }
+
+    /// Logs `message` followed by the time since `start`, e.g. "handled
+    /// request (took 1.23ms)".
+    #[track_caller]
+    pub fn with_level_elapsed(&self, level: Level, start: Instant, message: &str) {
+        self.with_level(level, &with_elapsed(message, start));
+    }
+
+    #[track_caller]
+    pub fn debug_elapsed(&self, start: Instant, message: &str) {
+        self.debug(&with_elapsed(message, start));
+    }
+
+    #[track_caller]
+    pub fn info_elapsed(&self, start: Instant, message: &str) {
+        self.info(&with_elapsed(message, start));
+    }
+
+    #[track_caller]
+    pub fn default_elapsed(&self, start: Instant, message: &str) {
+        self.default(&with_elapsed(message, start));
+    }
+
+    #[track_caller]
+    pub fn error_elapsed(&self, start: Instant, message: &str) {
+        self.error(&with_elapsed(message, start));
+    }
+
+    /// Logs `message` with `duration` in milliseconds as a separate numeric
+    /// os_log argument, rather than formatted in to the message. The memory
+    /// sink only records `message`.
+    #[track_caller]
+    pub fn with_level_duration(&self, level: Level, message: &str, duration: Duration) {
+        let message = self.locate(message);
+        let milliseconds = duration.as_secs_f64() * 1e3;
+        let _ = self.try_emit(level, &message, |message| unsafe {
+            match &self.image {
+                Some(image) => image.log_with_duration(self.inner, level, message, milliseconds),
+                None => wrapped_os_log_with_duration(
+                    self.inner,
+                    level as u8,
+                    message.as_ptr(),
+                    milliseconds,
+                ),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockLog;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(1500)), "1.50s");
+        assert_eq!(format_duration(Duration::from_micros(12_345)), "12.35ms");
+        assert_eq!(format_duration(Duration::from_nanos(4_250)), "4.25µs");
+        assert_eq!(format_duration(Duration::ZERO), "0.00µs");
+    }
+
+    #[test]
+    fn test_scope() {
+        let log = MockLog::new();
+        assert_eq!(scope(&log, "importing", || 42), 42);
+
+        let messages = log.messages();
+        assert_eq!(messages[0], (Level::Default, "importing: begin".into()));
+        assert!(messages[1].1.starts_with("importing: end (took "));
+
+        OsLog::new("com.example.oslog", "timing").scope("importing", || ());
+    }
+
+    #[test]
+    fn test_elapsed() {
+        let start = Instant::now() - Duration::from_secs(2);
+        let message = with_elapsed("handled request", start);
+        assert!(message.starts_with("handled request (took 2."));
+        assert!(message.ends_with("s)"));
+
+        let log = OsLog::new("com.example.oslog", "timing");
+        log.with_level_elapsed(Level::Info, start, "handled request");
+        log.debug_elapsed(start, "handled request");
+        log.info_elapsed(start, "handled request");
+        log.default_elapsed(start, "handled request");
+        log.error_elapsed(start, "handled request");
+        log.with_level_duration(Level::Info, "handled request", Duration::from_micros(1500));
+    }
+}
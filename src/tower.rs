@@ -0,0 +1,211 @@
+//! Logging HTTP requests handled by tower services, such as axum routers.
+//!
+//! ```no_run
+//! # fn wrap<S>(service: S) {
+//! use oslog::{tower::OsLogLayer, OsLog};
+//! use tower_layer::Layer;
+//!
+//! let layer = OsLogLayer::new(OsLog::new("com.example.test", "HTTP"));
+//! let service = layer.layer(service);
+//! # }
+//! ```
+
+use crate::sys::*;
+use crate::{Level, OsLog, SignpostId};
+use http::{Request, Response};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// The name of each request's signpost interval.
+const INTERVAL_NAME: &str = "Request";
+
+/// Logs the start and end of each request, runs it in a "Request" activity,
+/// and emits a signpost interval from when it's received until its response
+/// is ready, with the method, path, status and latency in its messages.
+#[derive(Clone)]
+pub struct OsLogLayer {
+    log: Arc<OsLog>,
+}
+
+impl OsLogLayer {
+    pub fn new(log: OsLog) -> Self {
+        Self { log: Arc::new(log) }
+    }
+}
+
+impl<S> Layer<S> for OsLogLayer {
+    type Service = OsLogService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        OsLogService {
+            inner,
+            log: Arc::clone(&self.log),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct OsLogService<S> {
+    inner: S,
+    log: Arc<OsLog>,
+}
+
+/// A request's activity, entered on the polling thread for each poll since
+/// activity scopes can't be held across threads.
+struct Activity(os_activity_t);
+
+// Activities are reference counted OS objects, usable from any thread.
+unsafe impl Send for Activity {}
+unsafe impl Sync for Activity {}
+
+impl Activity {
+    fn new() -> Self {
+        Self(unsafe { wrapped_os_activity_create_request() })
+    }
+
+    fn scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        let mut state = os_activity_scope_state_s::default();
+        unsafe { os_activity_scope_enter(self.0, &mut state) };
+        let result = f();
+        unsafe { os_activity_scope_leave(&mut state) };
+        result
+    }
+}
+
+impl Drop for Activity {
+    fn drop(&mut self) {
+        unsafe { os_release(self.0 as *mut _) };
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for OsLogService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        let activity = Activity::new();
+        let description = format!("{} {}", request.method(), request.uri().path());
+        let id = SignpostId::generate(&self.log);
+
+        let inner = activity.scope(|| {
+            self.log
+                .with_level(Level::Info, format_args!("Started {}", description));
+            self.log
+                .signpost_interval_begin(id, INTERVAL_NAME, &description);
+            self.inner.call(request)
+        });
+
+        ResponseFuture {
+            inner: Box::pin(inner),
+            log: Arc::clone(&self.log),
+            activity,
+            description,
+            id,
+            start: Instant::now(),
+        }
+    }
+}
+
+pub struct ResponseFuture<F> {
+    inner: Pin<Box<F>>,
+    log: Arc<OsLog>,
+    activity: Activity,
+    description: String,
+    id: SignpostId,
+    start: Instant,
+}
+
+impl<F, ResBody, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let inner = &mut this.inner;
+        let poll = this.activity.scope(|| inner.as_mut().poll(cx));
+
+        if let Poll::Ready(result) = &poll {
+            let latency = this.start.elapsed().as_secs_f64() * 1000.0;
+            let (level, outcome) = match result {
+                Ok(response) if response.status().is_server_error() => {
+                    (Level::Error, response.status().as_u16().to_string())
+                }
+                Ok(response) => (Level::Default, response.status().as_u16().to_string()),
+                Err(_) => (Level::Error, "failed".to_string()),
+            };
+            let message = format!("{} {} in {:.2}ms", this.description, outcome, latency);
+
+            this.activity.scope(|| {
+                this.log
+                    .with_level(level, format_args!("Finished {}", message));
+                this.log
+                    .signpost_interval_end(this.id, INTERVAL_NAME, &message);
+            });
+        }
+
+        poll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use std::future::{ready, Ready};
+    use std::task::{Wake, Waker};
+
+    struct Status(u16);
+
+    impl Service<Request<()>> for Status {
+        type Response = Response<()>;
+        type Error = Infallible;
+        type Future = Ready<Result<Response<()>, Infallible>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _request: Request<()>) -> Self::Future {
+            ready(Ok(Response::builder().status(self.0).body(()).unwrap()))
+        }
+    }
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    #[test]
+    fn test_layer() {
+        let layer = OsLogLayer::new(OsLog::new("com.example.oslog", "tower"));
+        let waker = Waker::from(Arc::new(NoopWaker));
+
+        for status in [200, 503] {
+            let mut service = layer.layer(Status(status));
+            let request = Request::get("/items?page=2").body(()).unwrap();
+            let mut future = service.call(request);
+
+            let mut cx = Context::from_waker(&waker);
+            match Pin::new(&mut future).poll(&mut cx) {
+                Poll::Ready(Ok(response)) => assert_eq!(response.status(), status),
+                _ => panic!("The response wasn't ready"),
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use crate::Error;
+use std::borrow::Cow;
+
+const ELLIPSIS: &str = "\u{2026}";
+
+/// What to do with messages longer than the configured maximum length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Truncation {
+    /// Cut the message short and end it with an ellipsis. If the maximum
+    /// length is too short for the ellipsis, it's cut short without one.
+    Ellipsis,
+    /// Split the message over as many entries as needed.
+    Chunk,
+    /// Don't log the message at all.
+    Error,
+}
+
+/// Returns the largest char boundary in `s` which is not greater than `index`.
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    if index >= s.len() {
+        return s.len();
+    }
+
+    let mut index = index;
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+
+    index
+}
+
+impl Truncation {
+    /// Splits `message` in to the parts which should be logged. Lengths are
+    /// measured in bytes, and messages are never split within a character.
+    pub(crate) fn apply(
+        self,
+        message: &str,
+        max_length: usize,
+    ) -> Result<Vec<Cow<'_, str>>, Error> {
+        if message.len() <= max_length {
+            return Ok(vec![Cow::Borrowed(message)]);
+        }
+
+        match self {
+            Self::Ellipsis if max_length < ELLIPSIS.len() => {
+                let end = floor_char_boundary(message, max_length);
+                Ok(vec![Cow::Borrowed(&message[..end])])
+            }
+            Self::Ellipsis => {
+                let end = floor_char_boundary(message, max_length.saturating_sub(ELLIPSIS.len()));
+                Ok(vec![Cow::Owned(format!("{}{}", &message[..end], ELLIPSIS))])
+            }
+            Self::Chunk => {
+                let mut chunks = Vec::new();
+                let mut rest = message;
+
+                while !rest.is_empty() {
+                    let mut end = floor_char_boundary(rest, max_length);
+
+                    // Always make progress, even if a single character is
+                    // longer than the maximum length.
+                    if end == 0 {
+                        end = rest.chars().next().map_or(rest.len(), char::len_utf8);
+                    }
+
+                    let (chunk, remainder) = rest.split_at(end);
+                    chunks.push(Cow::Borrowed(chunk));
+                    rest = remainder;
+                }
+
+                Ok(chunks)
+            }
+            Self::Error => Err(Error::MessageTooLong {
+                length: message.len(),
+                max_length,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_message_is_untouched() {
+        for policy in [Truncation::Ellipsis, Truncation::Chunk, Truncation::Error].iter() {
+            assert_eq!(policy.apply("Hello", 5).unwrap(), vec!["Hello"]);
+        }
+    }
+
+    #[test]
+    fn test_ellipsis() {
+        assert_eq!(
+            Truncation::Ellipsis.apply("Hello, world", 8).unwrap(),
+            vec!["Hello\u{2026}"]
+        );
+    }
+
+    #[test]
+    fn test_ellipsis_char_boundary() {
+        assert_eq!(
+            Truncation::Ellipsis.apply("ab\u{1F601}cd", 7).unwrap(),
+            vec!["ab\u{2026}"]
+        );
+    }
+
+    #[test]
+    fn test_ellipsis_too_long() {
+        assert_eq!(Truncation::Ellipsis.apply("Hello", 2).unwrap(), vec!["He"]);
+        assert_eq!(Truncation::Ellipsis.apply("Hello", 0).unwrap(), vec![""]);
+    }
+
+    #[test]
+    fn test_chunk() {
+        assert_eq!(
+            Truncation::Chunk.apply("Hello, world", 5).unwrap(),
+            vec!["Hello", ", wor", "ld"]
+        );
+    }
+
+    #[test]
+    fn test_chunk_char_boundary() {
+        assert_eq!(
+            Truncation::Chunk.apply("\u{1F601}\u{1F601}", 6).unwrap(),
+            vec!["\u{1F601}", "\u{1F601}"]
+        );
+        assert_eq!(
+            Truncation::Chunk.apply("\u{1F601}", 2).unwrap(),
+            vec!["\u{1F601}"]
+        );
+    }
+
+    #[test]
+    fn test_error() {
+        assert_eq!(
+            Truncation::Error.apply("Hello, world", 5),
+            Err(Error::MessageTooLong {
+                length: 12,
+                max_length: 5
+            })
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use std::borrow::Cow;
+use std::fmt::Write;
+
+/// What to do with invalid UTF-8 in messages built from bytes, `OsStr`s and
+/// `Path`s.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidUtf8 {
+    /// Replace invalid sequences with U+FFFD REPLACEMENT CHARACTER.
+    #[default]
+    Lossy,
+    /// Replace each invalid byte with its `\xNN` escape, so the original
+    /// bytes can be recovered from the message.
+    Escape,
+}
+
+impl InvalidUtf8 {
+    pub(crate) fn decode(self, bytes: &[u8]) -> Cow<'_, str> {
+        match self {
+            Self::Lossy => String::from_utf8_lossy(bytes),
+            Self::Escape => match std::str::from_utf8(bytes) {
+                Ok(valid) => Cow::Borrowed(valid),
+                Err(_) => Cow::Owned(escape(bytes)),
+            },
+        }
+    }
+}
+
+fn escape(mut bytes: &[u8]) -> String {
+    let mut escaped = String::with_capacity(bytes.len());
+
+    loop {
+        match std::str::from_utf8(bytes) {
+            Ok(valid) => {
+                escaped.push_str(valid);
+                return escaped;
+            }
+            Err(e) => {
+                let (valid, rest) = bytes.split_at(e.valid_up_to());
+                escaped.push_str(std::str::from_utf8(valid).unwrap());
+
+                let invalid_len = e.error_len().unwrap_or(rest.len());
+                for byte in &rest[..invalid_len] {
+                    write!(escaped, "\\x{:02x}", byte).unwrap();
+                }
+
+                bytes = &rest[invalid_len..];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_is_borrowed() {
+        assert!(matches!(
+            InvalidUtf8::Lossy.decode(b"Hi"),
+            Cow::Borrowed("Hi")
+        ));
+        assert!(matches!(
+            InvalidUtf8::Escape.decode(b"Hi"),
+            Cow::Borrowed("Hi")
+        ));
+    }
+
+    #[test]
+    fn test_lossy() {
+        assert_eq!(InvalidUtf8::Lossy.decode(b"a\xffb"), "a\u{FFFD}b");
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            InvalidUtf8::Escape.decode(b"a\xffb\xf0\x9f"),
+            "a\\xffb\\xf0\\x9f"
+        );
+        assert_eq!(
+            InvalidUtf8::Escape.decode("\u{1F601}\u{80}".as_bytes()),
+            "\u{1F601}\u{80}"
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{Level, OsLog, OsLogLike};
+use std::io::{self, Write};
+
+/// Buffers written bytes and logs each complete line as its own entry.
+/// Empty lines are skipped, invalid UTF-8 is replaced lossily, and any
+/// incomplete line is logged when the writer is flushed or dropped.
+pub struct OsLogWriter<L: OsLogLike = OsLog> {
+    log: L,
+    level: Level,
+    buffer: Vec<u8>,
+}
+
+impl<L: OsLogLike> OsLogWriter<L> {
+    pub fn new(log: L, level: Level) -> Self {
+        Self {
+            log,
+            level,
+            buffer: Vec::new(),
+        }
+    }
+
+    fn emit(&self, line: &[u8]) {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+        if !line.is_empty() {
+            self.log
+                .with_level(self.level, &String::from_utf8_lossy(line));
+        }
+    }
+}
+
+impl<L: OsLogLike> Write for OsLogWriter<L> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+
+        if let Some(end) = self.buffer.iter().rposition(|&b| b == b'\n') {
+            let complete: Vec<u8> = self.buffer.drain(..=end).collect();
+
+            for line in complete[..end].split(|&b| b == b'\n') {
+                self.emit(line);
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let rest = std::mem::take(&mut self.buffer);
+        crate::profile::measure("OsLogWriter::flush", || self.emit(&rest));
+        Ok(())
+    }
+}
+
+impl<L: OsLogLike> Drop for OsLogWriter<L> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockLog;
+
+    #[test]
+    fn test_lines() {
+        let log = MockLog::new();
+
+        {
+            let mut writer = OsLogWriter::new(&log, Level::Info);
+            write!(writer, "One\nTw").unwrap();
+            write!(writer, "o\r\n\nThree\nFo").unwrap();
+            assert_eq!(log.messages().len(), 3);
+        }
+
+        let messages: Vec<_> = log
+            .messages()
+            .into_iter()
+            .map(|(level, message)| {
+                assert_eq!(level, Level::Info);
+                message
+            })
+            .collect();
+
+        assert_eq!(messages, vec!["One", "Two", "Three", "Fo"]);
+    }
+
+    #[test]
+    fn test_flush() {
+        let log = MockLog::new();
+        let mut writer = OsLogWriter::new(&log, Level::Error);
+
+        writer.write_all(b"Partial \xff").unwrap();
+        assert!(log.messages().is_empty());
+
+        writer.flush().unwrap();
+        assert_eq!(
+            log.messages(),
+            vec![(Level::Error, "Partial \u{FFFD}".to_string())]
+        );
+
+        writer.flush().unwrap();
+        assert_eq!(log.messages().len(), 1);
+    }
+
+    #[test]
+    fn test_boxed_os_log() {
+        let mut writer: Box<dyn Write> = Box::new(OsLogWriter::new(
+            OsLog::new("com.example.oslog", "testing"),
+            Level::Debug,
+        ));
+
+        writeln!(writer, "Hello").unwrap();
+    }
+}
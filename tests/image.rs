@@ -0,0 +1,40 @@
+//! Logs attributed to another image record the same parts as any other,
+//! checked through the memory sink, which is global.
+
+use oslog::{log_with_location, sink, Level, OsLog, Truncation};
+use std::time::Duration;
+
+#[test]
+fn test_image_parts_recorded() {
+    let log = OsLog::new("com.example.oslog", "image")
+        .image(oslog::image!())
+        .truncation(Truncation::Chunk, 5);
+
+    sink::enable_memory(1024);
+
+    log.info("Hello, world");
+    log_with_location!(log, Level::Error, "Oops");
+    log.with_level_duration(Level::Info, "Done", Duration::from_millis(5));
+
+    let messages: Vec<_> = sink::drain()
+        .into_iter()
+        .map(|entry| (entry.level, entry.message))
+        .collect();
+    sink::disable_memory();
+
+    if cfg!(feature = "off") {
+        assert!(messages.is_empty());
+        return;
+    }
+
+    assert_eq!(
+        messages,
+        vec![
+            (Level::Info, "Hello".to_string()),
+            (Level::Info, ", wor".to_string()),
+            (Level::Info, "ld".to_string()),
+            (Level::Error, "Oops".to_string()),
+            (Level::Info, "Done".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,39 @@
+//! Links the public API as a dependent crate would. On targets other than
+//! Apple's, where wrapper.c isn't built, this only links if every FFI call
+//! has a stand-in.
+
+use oslog::{Level, OsLog, SignpostId};
+
+oslog::define_log!(LOG, "com.example.oslog", "link");
+
+#[test]
+fn test_logging() {
+    let log = OsLog::new("com.example.oslog", "link");
+    log.with_level(Level::Info, "Hello");
+    log.fault("Hello");
+    let _ = log.level_is_enabled(Level::Debug);
+
+    OsLog::global().default("Hello");
+    OsLog::disabled().default("Hello");
+    LOG.error("Hello");
+}
+
+#[test]
+fn test_signposts() {
+    let id = SignpostId::generate(&LOG);
+    LOG.signpost_event(id, "Event", "");
+    let _interval = LOG.signpost_interval("Interval");
+    let _ = oslog::signpost_here!(LOG);
+}
+
+#[test]
+fn test_facilities() {
+    oslog::breadcrumb("Linked");
+    oslog::thread::spawn_named("oslog-link", || {})
+        .unwrap()
+        .join()
+        .unwrap();
+
+    let lock = oslog::lock::SignpostMutex::new(0, &LOG, "Lock");
+    *lock.lock().unwrap() += 1;
+}
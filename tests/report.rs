@@ -0,0 +1,28 @@
+//! Installing sets the global eyre hook, so this has its own binary rather
+//! than changing how every report in the unit tests is formatted.
+
+#![cfg(any(feature = "anyhow", feature = "eyre"))]
+
+use oslog::{report, Error, OsLog};
+
+#[test]
+fn test_install() {
+    report::install(OsLog::new("com.example.oslog", "report")).unwrap();
+    assert_eq!(
+        report::install(OsLog::new("com.example.oslog", "report")),
+        Err(Error::AlreadyInstalled)
+    );
+
+    #[cfg(feature = "eyre")]
+    {
+        let report = eyre::eyre!("Oops").wrap_err("Couldn't do the thing");
+        assert!(format!("{:?}", report).contains("Oops"));
+    }
+
+    #[cfg(feature = "anyhow")]
+    {
+        let report = report::Report::from(anyhow::anyhow!("Oops").context("Couldn't do the thing"));
+        assert!(format!("{:?}", report).contains("Oops"));
+        assert_eq!(report.to_string(), "Couldn't do the thing");
+    }
+}
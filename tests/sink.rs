@@ -0,0 +1,39 @@
+//! The memory sink is global, so it's toggled in its own binary rather than
+//! while the unit tests are logging.
+
+use oslog::sink::{self, MemoryEntry};
+use oslog::{Level, OsLog};
+
+fn entry(message: &str) -> MemoryEntry {
+    MemoryEntry {
+        subsystem: "com.example.oslog".into(),
+        category: "sink".into(),
+        level: Level::Error,
+        message: message.into(),
+    }
+}
+
+#[test]
+fn test_memory_sink() {
+    let log = OsLog::new("com.example.oslog", "sink");
+
+    sink::enable_memory(1024);
+    assert!(sink::is_memory_enabled());
+
+    log.error("Two");
+    log.with_level(Level::Error, "Three");
+
+    if cfg!(feature = "off") {
+        assert!(sink::drain().is_empty());
+    } else {
+        assert_eq!(sink::entries(), vec![entry("Two"), entry("Three")]);
+        assert_eq!(sink::drain().len(), 2);
+        assert!(sink::entries().is_empty());
+    }
+
+    sink::disable_memory();
+    assert!(!sink::is_memory_enabled());
+
+    log.error("Four");
+    assert!(sink::entries().is_empty());
+}
@@ -0,0 +1,54 @@
+//! `OSLOG_STDERR` is read once per process, so this has its own binary.
+
+#![cfg(feature = "logger")]
+
+use log::{LevelFilter, Log, Record};
+use oslog::OsLogger;
+use std::io::{Read, Seek, SeekFrom};
+use std::os::unix::io::AsRawFd;
+
+#[test]
+#[cfg_attr(feature = "off", ignore = "nothing is emitted")]
+fn test_debug_reaches_stderr() {
+    std::env::set_var(oslog::sink::STDERR_VAR, "1");
+    assert!(oslog::sink::is_stderr_enabled());
+
+    let logger =
+        OsLogger::new("com.example.oslog").category_level_filter("stderr", LevelFilter::Trace);
+    let record = Record::builder()
+        .level(log::Level::Trace)
+        .target("stderr")
+        .args(format_args!("Traced"))
+        .build();
+
+    let mut file = tempfile();
+    let output = {
+        let original = unsafe { libc::dup(libc::STDERR_FILENO) };
+        unsafe { libc::dup2(file.as_raw_fd(), libc::STDERR_FILENO) };
+        logger.log(&record);
+        unsafe {
+            libc::dup2(original, libc::STDERR_FILENO);
+            libc::close(original);
+        }
+
+        let mut output = String::new();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file.read_to_string(&mut output).unwrap();
+        output
+    };
+
+    assert_eq!(output, "[com.example.oslog:stderr] Debug: Traced\n");
+}
+
+fn tempfile() -> std::fs::File {
+    let path = std::env::temp_dir().join(format!("oslog-stderr-{}", std::process::id()));
+    let file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path)
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    file
+}
@@ -1,13 +1,56 @@
+#include <os/activity.h>
 #include <os/log.h>
+#include <os/signpost.h>
+
+// The os_log macros record this file's &__dso_handle, so Console attributes
+// their messages to whichever image links the crate. See
+// wrapped_os_log_with_dso for attributing them to another.
 
 os_log_t wrapped_get_default_log() {
     return OS_LOG_DEFAULT;
 }
 
+os_log_t wrapped_get_disabled_log() {
+    return OS_LOG_DISABLED;
+}
+
 void wrapped_os_log_with_type(os_log_t log, os_log_type_t type, const char* message) {
     os_log_with_type(log, type, "%{public}s", message);
 }
 
+void wrapped_os_log_with_type_private(os_log_t log, os_log_type_t type, const char* message) {
+    os_log_with_type(log, type, "%{private}s", message);
+}
+
+// What os_log_with_type expands to, with the caller's image and format. The
+// system stores the format as an offset from dso, so it must be in that
+// image's __oslogstring section, and match the literal the arguments are
+// encoded with.
+#define OS_LOG_WITH_DSO(dso, log, type, format, literal, ...)                        \
+    do {                                                                             \
+        if (os_log_type_enabled(log, type)) {                                        \
+            uint8_t buffer[__builtin_os_log_format_buffer_size(literal, __VA_ARGS__)]; \
+            __builtin_os_log_format(buffer, literal, __VA_ARGS__);                   \
+            _os_log_impl(dso, log, type, format, buffer, sizeof(buffer));            \
+        }                                                                            \
+    } while (0)
+
+void wrapped_os_log_with_dso(void* dso, const char* format, os_log_t log, os_log_type_t type, const char* message) {
+    OS_LOG_WITH_DSO(dso, log, type, format, "%{public}s", message);
+}
+
+void wrapped_os_log_with_dso_private(void* dso, const char* format, os_log_t log, os_log_type_t type, const char* message) {
+    OS_LOG_WITH_DSO(dso, log, type, format, "%{private}s", message);
+}
+
+void wrapped_os_log_with_location_dso(void* dso, const char* format, os_log_t log, os_log_type_t type, const char* message, const char* module, const char* file, uint32_t line) {
+    OS_LOG_WITH_DSO(dso, log, type, format, "%{public}s [%{public}s %{public}s:%u]", message, module, file, line);
+}
+
+void wrapped_os_log_with_duration_dso(void* dso, const char* format, os_log_t log, os_log_type_t type, const char* message, double milliseconds) {
+    OS_LOG_WITH_DSO(dso, log, type, format, "%{public}s (took %.2fms)", message, milliseconds);
+}
+
 void wrapped_os_log_debug(os_log_t log, const char* message) {
     os_log_debug(log, "%{public}s", message);
 }
@@ -26,4 +69,66 @@ void wrapped_os_log_error(os_log_t log, const char* message) {
 
 void wrapped_os_log_fault(os_log_t log, const char* message) {
     os_log_fault(log, "%{public}s", message);
-}
\ No newline at end of file
+}
+
+void wrapped_os_log_with_location(os_log_t log, os_log_type_t type, const char* message, const char* module, const char* file, uint32_t line) {
+    os_log_with_type(log, type, "%{public}s [%{public}s %{public}s:%u]", message, module, file, line);
+}
+
+void wrapped_os_log_with_duration(os_log_t log, os_log_type_t type, const char* message, double milliseconds) {
+    os_log_with_type(log, type, "%{public}s (took %.2fms)", message, milliseconds);
+}
+
+// os_signpost requires the name to be a string literal, so every signpost
+// shares one of these and the caller's name is carried in the message.
+void wrapped_os_signpost_event_emit(os_log_t log, os_signpost_id_t id, const char* message) {
+    os_signpost_event_emit(log, id, "Event", "%{public}s", message);
+}
+
+void wrapped_os_signpost_interval_begin(os_log_t log, os_signpost_id_t id, const char* message) {
+    os_signpost_interval_begin(log, id, "Interval", "%{public}s", message);
+}
+
+void wrapped_os_signpost_interval_end(os_log_t log, os_signpost_id_t id, const char* message) {
+    os_signpost_interval_end(log, id, "Interval", "%{public}s", message);
+}
+
+void wrapped_os_signpost_counter(os_log_t log, os_signpost_id_t id, const char* key, double value) {
+    os_signpost_event_emit(log, id, "Counter", "%{public}s %f", key, value);
+}
+
+void wrapped_os_signpost_gauge(os_log_t log, os_signpost_id_t id, const char* key, double value) {
+    os_signpost_event_emit(log, id, "Gauge", "%{public}s %f", key, value);
+}
+
+void wrapped_os_signpost_histogram(os_log_t log, os_signpost_id_t id, const char* key, double value) {
+    os_signpost_event_emit(log, id, "Histogram", "%{public}s %f", key, value);
+}
+
+void wrapped_os_signpost_allocation(os_log_t log, os_signpost_id_t id, size_t size, size_t align) {
+    os_signpost_event_emit(log, id, "Allocation", "%zu bytes aligned to %zu", size, align);
+}
+
+void wrapped_os_signpost_interval_at(os_log_t log, os_signpost_id_t id, const char* message, uint64_t begin, uint64_t end) {
+    os_signpost_interval_begin(log, id, "Interval", "%{public}s %{public, signpost.description:begin_time}llu", message, begin);
+    os_signpost_interval_end(log, id, "Interval", "%{public, signpost.description:end_time}llu", end);
+}
+
+void wrapped_os_signpost_lock_wait(os_log_t log, os_signpost_id_t id, const char* message, uint64_t begin, uint64_t end, uint64_t wait_ns) {
+    os_signpost_interval_begin(log, id, "LockWait", "%{public}s %{public, signpost.description:begin_time}llu", message, begin);
+    os_signpost_interval_end(log, id, "LockWait", "%{public, signpost.description:end_time}llu waited %llu ns", end, wait_ns);
+}
+
+void wrapped_os_signpost_io(os_log_t log, os_signpost_id_t id, const char* message, uint64_t begin, uint64_t end, uint64_t bytes) {
+    os_signpost_interval_begin(log, id, "IO", "%{public}s %{public, signpost.description:begin_time}llu", message, begin);
+    os_signpost_interval_end(log, id, "IO", "%{public, signpost.description:end_time}llu %llu bytes", end, bytes);
+}
+
+// Like signpost names, activity descriptions must be string literals.
+os_activity_t wrapped_os_activity_create_trace(void) {
+    return os_activity_create("Trace", OS_ACTIVITY_CURRENT, OS_ACTIVITY_FLAG_DEFAULT);
+}
+
+os_activity_t wrapped_os_activity_create_request(void) {
+    return os_activity_create("Request", OS_ACTIVITY_CURRENT, OS_ACTIVITY_FLAG_DEFAULT);
+}